@@ -32,6 +32,18 @@ impl VanillaBlockEvents {
                 },
             )
     }
+
+    /// The fluid state inside the block state, or `None` if it's empty.
+    pub fn fluid_state(&self, state: &super::BlockState) -> Option<crate::fluid::SharedFluidState> {
+        let id = state.block().raw_id();
+        self.0
+            .iter()
+            .find(|e| e.0 == Some(id) && matches!(e.1, VanillaBlockCallback::FluidStateMap(_)))
+            .and_then(|e| match &e.1 {
+                VanillaBlockCallback::FluidStateMap(c) => c(state),
+                _ => unreachable!(),
+            })
+    }
 }
 
 /// An item event callback variant.
@@ -39,4 +51,10 @@ pub enum VanillaBlockCallback {
     BlockStateItemMap(
         Box<dyn Fn(super::BlockState) -> crate::item::ItemStack + 'static + Send + Sync>,
     ),
+    FluidStateMap(FluidStateMapper),
 }
+
+/// A callback mapping a block state to the fluid state inside it.
+pub type FluidStateMapper = Box<
+    dyn Fn(&super::BlockState) -> Option<crate::fluid::SharedFluidState> + 'static + Send + Sync,
+>;
@@ -1,4 +1,5 @@
 mod event;
+mod shape;
 
 use std::{hash::Hash, ops::Deref};
 
@@ -8,6 +9,7 @@ use crate::{
 };
 
 pub use event::*;
+pub use shape::*;
 
 //TODO: Build and freeze STATE_IDS
 
@@ -21,12 +23,55 @@ pub static STATE_IDS: once_cell::sync::Lazy<
 pub struct Block {
     id: usize,
     pub states: crate::util::StaticRef<crate::state::States<BlockState>>,
+    properties: BlockDescriptor,
+}
+
+/// Describes some basic properties of a block.
+#[derive(Clone, Copy)]
+pub struct BlockDescriptor {
+    /// How much light this block blocks, from 0 to 15.
+    pub opacity: u8,
+    /// Whether the shape of this block is a full cube.
+    pub full_cube: bool,
+}
+
+impl BlockDescriptor {
+    /// Whether this block fully blocks light and vision.
+    pub fn is_opaque(&self) -> bool {
+        self.opacity >= 15
+    }
+
+    /// Whether the given face of this block covers the whole side.
+    ///
+    /// Only full cubes are supported for now, which cover every face.
+    pub fn is_face_full(&self, _face: crate::util::math::Direction) -> bool {
+        self.full_cube
+    }
+}
+
+impl Default for BlockDescriptor {
+    fn default() -> Self {
+        Self {
+            opacity: 15,
+            full_cube: true,
+        }
+    }
 }
 
 impl Block {
+    /// Creates a block with the default [`BlockDescriptor`],
+    /// which is an opaque full cube.
     pub fn new(states: Vec<(crate::state::property::Property, u8)>) -> anyhow::Result<Self> {
+        Self::with_descriptor(BlockDescriptor::default(), states)
+    }
+
+    pub fn with_descriptor(
+        descriptor: BlockDescriptor,
+        states: Vec<(crate::state::property::Property, u8)>,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
             id: 0,
+            properties: descriptor,
             states: {
                 let mut builder = crate::state::StatesBuilder::new();
                 let mut map = hashbrown::HashMap::new();
@@ -39,6 +84,32 @@ impl Block {
             .into(),
         })
     }
+
+    pub fn descriptor(&self) -> &BlockDescriptor {
+        &self.properties
+    }
+}
+
+/// Whether the `face` of a block should be rendered, with the
+/// `neighbor_state` being the adjacent block towards the face.
+///
+/// Faces adjacent to opaque full faces are culled, and so are faces
+/// between two identical transparent blocks, like glass.
+pub fn should_render_face(
+    this_state: &BlockState,
+    neighbor_state: &BlockState,
+    face: crate::util::math::Direction,
+) -> bool {
+    let neighbor = neighbor_state.block();
+    let descriptor = neighbor.descriptor();
+
+    if descriptor.is_opaque() && descriptor.is_face_full(face.opposite()) {
+        false
+    } else {
+        !(this_state.block() == neighbor
+            && !descriptor.is_opaque()
+            && descriptor.is_face_full(face.opposite()))
+    }
 }
 
 impl Registration for Block {
@@ -121,6 +192,7 @@ impl Hash for Block {
 pub struct BlockState {
     block: std::sync::atomic::AtomicUsize,
     state: crate::state::State,
+    collision_shape: once_cell::sync::OnceCell<VoxelShape>,
 }
 
 impl BlockState {
@@ -131,6 +203,27 @@ impl BlockState {
             .unwrap()
             .deref()
     }
+
+    /// The collision shape of this state.
+    ///
+    /// Defaults to a full cube or an empty shape depending on
+    /// [`BlockDescriptor::full_cube`] if no shape has been set.
+    pub fn collision_shape(&self) -> &VoxelShape {
+        self.collision_shape.get().unwrap_or_else(|| {
+            if self.block().descriptor().full_cube {
+                &*FULL_CUBE
+            } else {
+                &EMPTY
+            }
+        })
+    }
+
+    /// Set the collision shape of this state, which can only be set once.
+    pub fn set_collision_shape(&self, shape: VoxelShape) -> anyhow::Result<()> {
+        self.collision_shape
+            .set(shape)
+            .map_err(|_| anyhow::anyhow!("Collision shape of this state has already been set"))
+    }
 }
 
 impl From<((), crate::state::State)> for BlockState {
@@ -138,6 +231,7 @@ impl From<((), crate::state::State)> for BlockState {
         Self {
             block: std::sync::atomic::AtomicUsize::new(0),
             state: value,
+            collision_shape: once_cell::sync::OnceCell::new(),
         }
     }
 }
@@ -152,3 +246,59 @@ impl Deref for BlockState {
 
 /// A shared [`BlockState`] with states reference count and the index.
 pub type SharedBlockState = crate::state::Shared<BlockState>;
+
+#[cfg(test)]
+mod tests {
+    use crate::util::math::Direction;
+
+    use super::*;
+
+    fn default_state(path: &str) -> SharedBlockState {
+        crate::registry::freeze_for_tests();
+        let block = *crate::registry::BLOCK
+            .get_from_id(&Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap())
+            .unwrap()
+            .1
+            .deref();
+        crate::state::States::get_shared(block.states, 0)
+    }
+
+    #[test]
+    fn stone_next_to_stone_is_culled() {
+        let stone = default_state("stone");
+        for face in Direction::values() {
+            assert!(!should_render_face(&stone, &stone, face));
+        }
+    }
+
+    #[test]
+    fn stone_next_to_air_is_rendered() {
+        let stone = default_state("stone");
+        let air = default_state("air");
+        for face in Direction::values() {
+            assert!(should_render_face(&stone, &air, face));
+        }
+    }
+
+    #[test]
+    fn transparent_neighbors() {
+        let stone = default_state("stone");
+        let glass = default_state("glass");
+        assert!(!should_render_face(&glass, &glass, Direction::Up));
+        assert!(should_render_face(&stone, &glass, Direction::Up));
+        assert!(!should_render_face(&glass, &stone, Direction::Up));
+    }
+
+    #[test]
+    fn default_descriptor_is_opaque_full_cube() {
+        let block = Block::new(Vec::new()).unwrap();
+        assert!(block.descriptor().is_opaque());
+        assert!(block.descriptor().full_cube);
+    }
+
+    #[test]
+    fn default_collision_shape_follows_full_cube() {
+        assert!(default_state("stone").collision_shape() == &*FULL_CUBE);
+        assert!(default_state("water").collision_shape().is_empty());
+    }
+}
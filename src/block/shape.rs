@@ -0,0 +1,229 @@
+use crate::{prelude::*, util::math::Direction};
+
+/// The shape of a full cube.
+pub static FULL_CUBE: once_cell::sync::Lazy<VoxelShape> =
+    once_cell::sync::Lazy::new(|| VoxelShape::from_box(glam::DVec3::ZERO, glam::DVec3::ONE));
+
+/// The empty shape.
+pub static EMPTY: VoxelShape = VoxelShape::empty();
+
+/// A shape made of a union of boxes, in block-local coords
+/// where a full cube spans from `0` to `1`.
+#[derive(Clone, PartialEq, Default)]
+pub struct VoxelShape {
+    boxes: Vec<crate::util::math::Box>,
+}
+
+impl VoxelShape {
+    pub const fn empty() -> Self {
+        Self { boxes: Vec::new() }
+    }
+
+    /// Creates a shape of a single box with the given corners.
+    pub fn from_box(min: glam::DVec3, max: glam::DVec3) -> Self {
+        let b = crate::util::math::Box::new(min, max);
+        if b.min_x < b.max_x && b.min_y < b.max_y && b.min_z < b.max_z {
+            Self { boxes: vec![b] }
+        } else {
+            Self::empty()
+        }
+    }
+
+    /// Union of this shape and the other shape.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut boxes = self.boxes.clone();
+        boxes.extend(other.boxes.iter().filter(|e| !self.boxes.contains(e)));
+        Self { boxes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    /// Boxes of this shape.
+    pub fn boxes(&self) -> &[crate::util::math::Box] {
+        &self.boxes
+    }
+
+    /// The smallest box containing this shape, or `None` if it's empty.
+    pub fn bounding_box(&self) -> Option<crate::util::math::Box> {
+        self.boxes.iter().copied().reduce(|a, b| a.union(b))
+    }
+
+    /// Cast a ray from `start` to `end` in world coords against this shape
+    /// placed at the block `pos`, returning the nearest hit.
+    pub fn raycast(
+        &self,
+        start: glam::DVec3,
+        end: glam::DVec3,
+        pos: BlockPos,
+    ) -> Option<crate::world::BlockHit> {
+        let offset = pos.as_dvec3();
+        let delta = end - start;
+
+        self.boxes
+            .iter()
+            .filter_map(|b| {
+                let min = glam::DVec3::new(b.min_x, b.min_y, b.min_z) + offset;
+                let max = glam::DVec3::new(b.max_x, b.max_y, b.max_z) + offset;
+                ray_box(start, delta, min, max)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(t, side)| crate::world::BlockHit {
+                pos,
+                side,
+                hit: start + delta * t,
+            })
+    }
+}
+
+/// Intersect a ray with a box using the slab method, returning
+/// the entering fraction of the ray in `0..=1` and the face entered.
+fn ray_box(
+    start: glam::DVec3,
+    delta: glam::DVec3,
+    min: glam::DVec3,
+    max: glam::DVec3,
+) -> Option<(f64, Direction)> {
+    let mut t_enter = 0.0;
+    let mut t_exit = 1.0;
+    let mut side = None;
+
+    let axes = [
+        (
+            start.x,
+            delta.x,
+            min.x,
+            max.x,
+            Direction::West,
+            Direction::East,
+        ),
+        (
+            start.y,
+            delta.y,
+            min.y,
+            max.y,
+            Direction::Down,
+            Direction::Up,
+        ),
+        (
+            start.z,
+            delta.z,
+            min.z,
+            max.z,
+            Direction::North,
+            Direction::South,
+        ),
+    ];
+
+    for (s, d, lo, hi, lo_side, hi_side) in axes {
+        if d == 0.0 {
+            if s < lo || s > hi {
+                return None;
+            }
+            continue;
+        }
+
+        let (t0, t1, face) = if d > 0.0 {
+            ((lo - s) / d, (hi - s) / d, lo_side)
+        } else {
+            ((hi - s) / d, (lo - s) / d, hi_side)
+        };
+
+        if t0 > t_enter {
+            t_enter = t0;
+            side = Some(face);
+        }
+        if t1 < t_exit {
+            t_exit = t1;
+        }
+        if t_enter > t_exit {
+            return None;
+        }
+    }
+
+    // starting inside the box hits the face opposite to the ray direction
+    let side = side.unwrap_or_else(|| {
+        let abs = delta.abs();
+        if abs.x >= abs.y && abs.x >= abs.z {
+            if delta.x > 0.0 {
+                Direction::West
+            } else {
+                Direction::East
+            }
+        } else if abs.y >= abs.z {
+            if delta.y > 0.0 {
+                Direction::Down
+            } else {
+                Direction::Up
+            }
+        } else if delta.z > 0.0 {
+            Direction::North
+        } else {
+            Direction::South
+        }
+    });
+
+    Some((t_enter, side))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slab() -> VoxelShape {
+        VoxelShape::from_box(glam::DVec3::ZERO, glam::DVec3::new(1.0, 0.5, 1.0))
+    }
+
+    #[test]
+    fn raycast_hits_slab_top() {
+        let pos = BlockPos::new(2, 10, -3);
+        let base = pos.as_dvec3();
+        let hit = slab()
+            .raycast(
+                base + glam::DVec3::new(0.5, 2.0, 0.5),
+                base + glam::DVec3::new(0.5, -1.0, 0.5),
+                pos,
+            )
+            .unwrap();
+
+        assert!(hit.side == Direction::Up);
+        assert!(hit.pos == pos);
+        assert_eq!(hit.hit, base + glam::DVec3::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn raycast_misses_above_slab() {
+        let pos = BlockPos::new(0, 0, 0);
+        assert!(slab()
+            .raycast(
+                glam::DVec3::new(-1.0, 0.75, 0.5),
+                glam::DVec3::new(2.0, 0.75, 0.5),
+                pos,
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn union_skips_duplicates_and_bounds_both() {
+        let top = VoxelShape::from_box(glam::DVec3::new(0.0, 0.5, 0.0), glam::DVec3::ONE);
+        let union = slab().union(&top).union(&slab());
+
+        assert_eq!(union.boxes().len(), 2);
+        assert!(union.bounding_box() == FULL_CUBE.bounding_box());
+    }
+
+    #[test]
+    fn degenerate_box_is_empty() {
+        let flat = VoxelShape::from_box(glam::DVec3::ZERO, glam::DVec3::new(1.0, 0.0, 1.0));
+        assert!(flat.is_empty());
+        assert!(flat.bounding_box().is_none());
+        assert!(EMPTY
+            .raycast(
+                glam::DVec3::NEG_ONE,
+                glam::DVec3::ONE,
+                BlockPos::new(0, 0, 0)
+            )
+            .is_none());
+    }
+}
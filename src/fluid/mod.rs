@@ -32,6 +32,50 @@ impl Fluid {
     pub fn id(&self) -> usize {
         self.id
     }
+
+    /// States of this fluid.
+    pub fn states(&self) -> &crate::state::States<FluidState> {
+        &self.states
+    }
+
+    /// The filled bucket item of this fluid.
+    pub fn bucket_item(&self) -> Option<crate::item::Item> {
+        BUCKETS
+            .read()
+            .iter()
+            .find(|e| e.0 == self.id)
+            .and_then(|e| crate::registry::ITEM.get_from_raw(e.1))
+            .map(|e| *e.deref())
+    }
+}
+
+/// Fluid to filled bucket item mapping in raw ids.
+pub static BUCKETS: parking_lot::RwLock<Vec<(usize, usize)>> = parking_lot::RwLock::new(Vec::new());
+
+/// Map a fluid to its filled bucket item and the reverse,
+/// replacing existing mappings of both.
+pub fn register_bucket(fluid: &Fluid, item: crate::item::Item) {
+    let mut buckets = BUCKETS.write();
+    buckets.retain(|e| e.0 != fluid.id && e.1 != item.raw_id());
+    buckets.push((fluid.id, item.raw_id()));
+}
+
+/// Register vanilla buckets for `water`, `lava` and `empty`
+/// if these fluids and items exist in registries.
+pub fn register_vanilla_buckets() {
+    let id = |path| Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap();
+    for (fluid, item) in [
+        ("water", "water_bucket"),
+        ("lava", "lava_bucket"),
+        ("empty", "bucket"),
+    ] {
+        if let (Some(f), Some(i)) = (
+            crate::registry::FLUID.get_from_id(&id(fluid)),
+            crate::registry::ITEM.get_from_id(&id(item)),
+        ) {
+            register_bucket(f.1.deref(), *i.1.deref());
+        }
+    }
 }
 
 impl Registration for Fluid {
@@ -133,3 +177,6 @@ impl Deref for FluidState {
         &self.state
     }
 }
+
+/// A shared [`FluidState`] with states reference count and the index.
+pub type SharedFluidState = crate::state::Shared<FluidState>;
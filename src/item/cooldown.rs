@@ -0,0 +1,104 @@
+use super::Item;
+
+/// Manager of item cooldowns, like the ender pearl cooldown of a player.
+#[derive(Default)]
+pub struct ItemCooldownManager {
+    /// Item to (start tick, duration) mapping.
+    entries: hashbrown::HashMap<Item, (u64, u32)>,
+}
+
+impl ItemCooldownManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a cooldown for the item with `duration` ticks,
+    /// starting from `current_tick`.
+    pub fn set(&mut self, item: Item, duration: u32, current_tick: u64) {
+        self.entries.insert(item, (current_tick, duration));
+    }
+
+    /// Remove the cooldown of the item.
+    pub fn remove(&mut self, item: Item) {
+        self.entries.remove(&item);
+    }
+
+    /// Whether the item is on cooldown at `current_tick`.
+    pub fn is_on_cooldown(&mut self, item: Item, current_tick: u64) -> bool {
+        self.progress(item, current_tick) > 0.0
+    }
+
+    /// Remaining progress of the cooldown of the item at `current_tick`,
+    /// from `1.0` (just started) to `0.0` (not on cooldown).
+    ///
+    /// Expired entries are removed from this manager.
+    pub fn progress(&mut self, item: Item, current_tick: u64) -> f32 {
+        let Some((start, duration)) = self.entries.get(&item).copied() else {
+            return 0.0;
+        };
+
+        let end = start + duration as u64;
+        if current_tick >= end || duration == 0 {
+            self.entries.remove(&item);
+            0.0
+        } else {
+            (end - current_tick.max(start)) as f32 / duration as f32
+        }
+    }
+
+    /// Remove all expired entries at `current_tick`.
+    pub fn update(&mut self, current_tick: u64) {
+        self.entries
+            .retain(|_, (start, duration)| current_tick < *start + *duration as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str) -> Item {
+        crate::registry::freeze_for_tests();
+        *crate::registry::ITEM
+            .get_from_id(
+                &crate::util::Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap(),
+            )
+            .unwrap()
+            .1
+            .value()
+    }
+
+    #[test]
+    fn progress_decreases_until_expiry() {
+        let apple = item("apple");
+        let mut manager = ItemCooldownManager::new();
+        manager.set(apple, 20, 100);
+
+        assert_eq!(manager.progress(apple, 100), 1.0);
+        assert_eq!(manager.progress(apple, 110), 0.5);
+        assert!(manager.is_on_cooldown(apple, 119));
+        assert!(!manager.is_on_cooldown(apple, 120));
+        assert!(manager.entries.is_empty());
+    }
+
+    #[test]
+    fn update_drops_expired_entries() {
+        let (apple, stone) = (item("apple"), item("stone"));
+        let mut manager = ItemCooldownManager::new();
+        manager.set(apple, 10, 0);
+        manager.set(stone, 50, 0);
+
+        manager.update(30);
+        assert!(!manager.entries.contains_key(&apple));
+        assert!(manager.is_on_cooldown(stone, 30));
+    }
+
+    #[test]
+    fn remove_clears_cooldown() {
+        let apple = item("apple");
+        let mut manager = ItemCooldownManager::new();
+        manager.set(apple, 10, 0);
+        manager.remove(apple);
+        assert_eq!(manager.progress(apple, 0), 0.0);
+    }
+}
@@ -2,23 +2,73 @@ use crate::registry::Registration;
 
 /// Vanilla item events for perform item actions and obtain item settings.
 pub static EVENTS: parking_lot::RwLock<VanillaItemEvents> =
-    parking_lot::RwLock::new(VanillaItemEvents(vec![]));
+    parking_lot::RwLock::new(VanillaItemEvents::new());
+
+/// Register a stack size tag into [`EVENTS`].
+///
+/// See [`VanillaItemEvents::register_stack_size_tag`].
+pub fn register_stack_size_tag(tag: crate::registry::tag::TagKey<super::Item>, size: u8) {
+    EVENTS.write().register_stack_size_tag(tag, size);
+}
 
 /// Manager for item events.
-pub struct VanillaItemEvents(Vec<(Option<usize>, VanillaItemCallback)>);
+pub struct VanillaItemEvents {
+    /// Callbacks with the raw id of the item they apply to,
+    /// or `None` for all items.
+    callbacks: Vec<(Option<usize>, VanillaItemCallback)>,
+    /// Nbt processors with optional namespace filters.
+    nbt_processors: Vec<(Option<String>, NbtProcessor)>,
+    /// Item tags overriding max counts of items inside them.
+    stack_size_tags: Vec<(crate::registry::tag::TagKey<super::Item>, u8)>,
+}
+
+/// A callback for post-processing item stack nbt.
+pub type NbtProcessor = Box<dyn Fn(&mut crate::nbt::NbtCompound) + 'static + Send + Sync>;
+
+/// A callback validating an item stack, returning its problems.
+pub type ItemValidator =
+    Box<dyn Fn(&super::ItemStack) -> Result<(), Vec<String>> + 'static + Send + Sync>;
 
 impl VanillaItemEvents {
+    /// Creates an instance without any callbacks.
+    pub const fn new() -> Self {
+        Self {
+            callbacks: Vec::new(),
+            nbt_processors: Vec::new(),
+            stack_size_tags: Vec::new(),
+        }
+    }
+
     /// Register a callback into this instance.
     ///
     /// The required `item` can be `None` for some events
     /// so that all items will be affected by this callback.
     pub fn register(&mut self, item: Option<super::Item>, callback: VanillaItemCallback) {
-        self.0.push((item.map(|e| e.raw_id()), callback));
+        self.callbacks.push((item.map(|e| e.raw_id()), callback));
+    }
+
+    /// Register an nbt processor which only runs for items in the
+    /// given `namespace`, or for all items if `namespace` is `None`.
+    pub fn add_nbt_processor(&mut self, namespace: Option<&str>, processor: NbtProcessor) {
+        self.nbt_processors
+            .push((namespace.map(str::to_string), processor));
+    }
+
+    /// Register a tag so that items in it have the given max count,
+    /// taking precedence over [`VanillaItemCallback::GetMaxCount`] callbacks.
+    ///
+    /// The smallest size is used if an item is in multiple registered tags.
+    pub fn register_stack_size_tag(
+        &mut self,
+        tag: crate::registry::tag::TagKey<super::Item>,
+        size: u8,
+    ) {
+        self.stack_size_tags.push((tag, size));
     }
 
     pub fn get_max_damage(&self, stack: &super::ItemStack) -> u32 {
         let id = stack.item.raw_id();
-        self.0
+        self.callbacks
             .iter()
             .find(|e| {
                 e.0.map_or(false, |ee| ee == id)
@@ -32,7 +82,18 @@ impl VanillaItemEvents {
 
     pub fn get_max_count(&self, stack: &super::ItemStack) -> u8 {
         let id = stack.item.raw_id();
-        self.0
+
+        if let Some(size) = self
+            .stack_size_tags
+            .iter()
+            .filter(|e| crate::registry::ITEM.is_in_tag(id, &e.0))
+            .map(|e| e.1)
+            .min()
+        {
+            return size;
+        }
+
+        self.callbacks
             .iter()
             .find(|e| {
                 e.0.map_or(false, |ee| ee == id)
@@ -44,9 +105,31 @@ impl VanillaItemEvents {
             })
     }
 
+    /// Run all validators for the stack and collect their problems.
+    pub fn validate(&self, stack: &super::ItemStack) -> Result<(), Vec<String>> {
+        let id = stack.item.raw_id();
+        let problems = self
+            .callbacks
+            .iter()
+            .filter(|e| {
+                e.0.is_none_or(|ee| ee == id) && matches!(e.1, VanillaItemCallback::Validate(_))
+            })
+            .flat_map(|e| match &e.1 {
+                VanillaItemCallback::Validate(c) => c(stack).err().unwrap_or_default(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
     pub fn post_process_nbt(&self, item: super::Item, nbt: &mut crate::nbt::NbtCompound) {
         let id = item.raw_id();
-        self.0
+        self.callbacks
             .iter()
             .filter(|e| {
                 e.0.map_or(true, |ee| ee == id)
@@ -55,7 +138,28 @@ impl VanillaItemEvents {
             .for_each(|e| match &e.1 {
                 VanillaItemCallback::PostProcessNbt(c) => c(nbt),
                 _ => unreachable!(),
+            });
+
+        if self.nbt_processors.is_empty() {
+            return;
+        }
+
+        let item_id = crate::registry::ITEM
+            .get_from_raw(id)
+            .map(|e| e.key().value().clone());
+        self.nbt_processors
+            .iter()
+            .filter(|e| {
+                e.0.as_ref()
+                    .is_none_or(|ns| item_id.as_ref().is_some_and(|id| id.is_namespace(ns)))
             })
+            .for_each(|e| (e.1)(nbt))
+    }
+}
+
+impl Default for VanillaItemEvents {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -64,4 +168,94 @@ pub enum VanillaItemCallback {
     GetMaxCount(Box<dyn Fn(&super::ItemStack) -> u8 + 'static + Send + Sync>),
     GetMaxDamage(Box<dyn Fn(&super::ItemStack) -> u32 + 'static + Send + Sync>),
     PostProcessNbt(Box<dyn Fn(&mut crate::nbt::NbtCompound) + 'static + Send + Sync>),
+    /// Validate a stack, returning problems like missing nbt keys.
+    Validate(ItemValidator),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn item(id: &str) -> crate::item::Item {
+        crate::registry::freeze_for_tests();
+        *crate::registry::ITEM
+            .get_from_id(&Identifier::parse(id))
+            .unwrap()
+            .1
+            .value()
+    }
+
+    #[test]
+    fn nbt_processors_filtered_by_namespace() {
+        let mut events = VanillaItemEvents::new();
+        events.add_nbt_processor(
+            Some("mymod"),
+            Box::new(|nbt| nbt.insert_bool("mymod", true)),
+        );
+        events.add_nbt_processor(None, Box::new(|nbt| nbt.insert_bool("global", true)));
+
+        let mut nbt = crate::nbt::NbtCompound::new();
+        events.post_process_nbt(item("minecraft:stone"), &mut nbt);
+        assert_eq!(nbt.get_bool("mymod"), None);
+        assert_eq!(nbt.get_bool("global"), Some(true));
+
+        let mut nbt = crate::nbt::NbtCompound::new();
+        events.post_process_nbt(item("mymod:gem"), &mut nbt);
+        assert_eq!(nbt.get_bool("mymod"), Some(true));
+        assert_eq!(nbt.get_bool("global"), Some(true));
+    }
+
+    #[test]
+    fn validator_flags_missing_key() {
+        let book = item("mymod:gem");
+        let mut events = VanillaItemEvents::new();
+        events.register(
+            Some(book),
+            VanillaItemCallback::Validate(Box::new(|stack| {
+                match stack.nbt.as_ref().map(|nbt| nbt.get("pages")) {
+                    Some(Some(crate::nbt::NbtElement::List(_))) => Ok(()),
+                    Some(Some(_)) => Err(vec!["pages is not a list".to_string()]),
+                    _ => Err(vec!["missing pages".to_string()]),
+                }
+            })),
+        );
+
+        let mut stack = crate::item::ItemStack::new(&book, 1);
+        assert_eq!(
+            events.validate(&stack),
+            Err(vec!["missing pages".to_string()])
+        );
+        stack.get_or_init_nbt().insert_i32("pages", 1);
+        assert_eq!(
+            events.validate(&stack),
+            Err(vec!["pages is not a list".to_string()])
+        );
+        stack.get_or_init_nbt().insert_list("pages", Vec::new());
+        assert_eq!(events.validate(&stack), Ok(()));
+
+        // other items are valid by default
+        let stone = crate::item::ItemStack::new(&item("minecraft:stone"), 1);
+        assert_eq!(events.validate(&stone), Ok(()));
+        assert_eq!(stone.validate(), Ok(()));
+    }
+
+    #[test]
+    fn stack_size_tag_overrides_max_count() {
+        let bucket = item("minecraft:lava_bucket");
+        // bound to the lava bucket by the shared test registries
+        let tag = crate::registry::tag::TagKey::new(
+            crate::registry::ITEM.key.clone(),
+            Identifier::parse("c:unstackable"),
+        );
+        let stack = crate::item::ItemStack::new(&bucket, 1);
+        let stone = crate::item::ItemStack::new(&item("minecraft:stone"), 1);
+
+        let mut events = VanillaItemEvents::new();
+        assert_eq!(events.get_max_count(&stack), 64);
+        events.register_stack_size_tag(tag.clone(), 16);
+        events.register_stack_size_tag(tag, 1);
+        assert_eq!(events.get_max_count(&stack), 1);
+        assert_eq!(events.get_max_count(&stone), 64);
+    }
 }
@@ -0,0 +1,440 @@
+use crate::{
+    prelude::*,
+    random::JavaRandom,
+    registry::{tag::TagKey, Registry},
+};
+
+use super::{Item, ItemStack};
+
+/// An inclusive range of integers to pick randomly from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IntRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl IntRange {
+    pub fn new(min: i32, max: i32) -> Self {
+        Self { min, max }
+    }
+
+    /// A range always producing the given value.
+    pub fn constant(value: i32) -> Self {
+        Self::new(value, value)
+    }
+
+    /// Pick a random value in this range, or the minimum if the range is empty
+    /// or has more values than an `i32` can count, which vanilla can't sample.
+    pub fn sample(&self, rng: &mut JavaRandom) -> i32 {
+        if self.max <= self.min {
+            return self.min;
+        }
+
+        match self.span() {
+            Some(span) => self.min + rng.next_i32_bounded(span),
+            None => {
+                tracing::warn!("Range {}..={} is too wide to sample", self.min, self.max);
+                self.min
+            }
+        }
+    }
+
+    /// Number of values in this range, or `None` if it doesn't fit in an `i32`.
+    fn span(&self) -> Option<i32> {
+        i32::try_from(self.max as i64 - self.min as i64 + 1).ok()
+    }
+
+    /// Parse a range from a number or a `{ "min": _, "max": _ }` object.
+    pub fn from_json(json: &serde_json::Value) -> anyhow::Result<Self> {
+        if let Some(value) = json.as_f64() {
+            return Ok(Self::constant(value as i32));
+        }
+
+        let get = |key: &str| {
+            json.get(key)
+                .and_then(serde_json::Value::as_f64)
+                .map(|e| e as i32)
+                .ok_or_else(|| anyhow::anyhow!("Missing {key} in number provider: {json}"))
+        };
+        let range = Self::new(get("min")?, get("max")?);
+        if range.max > range.min && range.span().is_none() {
+            return Err(anyhow::anyhow!("Number provider range too wide: {json}"));
+        }
+        Ok(range)
+    }
+}
+
+impl Default for IntRange {
+    fn default() -> Self {
+        Self::constant(1)
+    }
+}
+
+/// An entry of a [`LootPool`].
+#[derive(Clone, PartialEq)]
+pub enum LootEntry {
+    /// A stack of the item with a random count.
+    Item {
+        item: Item,
+        count: IntRange,
+        weight: u32,
+    },
+    /// A stack of a random member of the tag.
+    Tag {
+        tag: TagKey<Item>,
+        count: IntRange,
+        weight: u32,
+    },
+    /// A random child picked by weights.
+    Group {
+        children: Vec<LootEntry>,
+        weight: u32,
+    },
+}
+
+impl LootEntry {
+    pub fn weight(&self) -> u32 {
+        match self {
+            LootEntry::Item { weight, .. }
+            | LootEntry::Tag { weight, .. }
+            | LootEntry::Group { weight, .. } => *weight,
+        }
+    }
+
+    /// Roll this entry, returning `None` if nothing is produced.
+    pub fn roll(&self, rng: &mut JavaRandom, registry: &Registry<Item>) -> Option<ItemStack> {
+        match self {
+            LootEntry::Item { item, count, .. } => Some(ItemStack::new(
+                item,
+                count.sample(rng).clamp(0, u8::MAX as i32) as u8,
+            )),
+            LootEntry::Tag { tag, count, .. } => {
+                let ids = registry.tag_entries(tag);
+                if ids.is_empty() {
+                    return None;
+                }
+
+                let id = ids[rng.next_i32_bounded(ids.len() as i32) as usize];
+                Some(ItemStack::new(
+                    registry.get_from_raw(id)?,
+                    count.sample(rng).clamp(0, u8::MAX as i32) as u8,
+                ))
+            }
+            LootEntry::Group { children, .. } => {
+                pick_weighted(children, rng).and_then(|e| e.roll(rng, registry))
+            }
+        }
+    }
+
+    /// Parse an entry in the vanilla loot table format.
+    ///
+    /// Only the `set_count` function is supported, and others are ignored.
+    pub fn from_json(json: &serde_json::Value, registry: &Registry<Item>) -> anyhow::Result<Self> {
+        let weight = match json.get("weight").and_then(serde_json::Value::as_u64) {
+            Some(weight) => u32::try_from(weight)
+                .map_err(|_| anyhow::anyhow!("Loot entry weight too large: {weight}"))?,
+            None => 1,
+        };
+        let name = || {
+            json.get("name")
+                .and_then(serde_json::Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("Missing name in loot entry: {json}"))
+                .and_then(Identifier::try_parse)
+        };
+
+        let mut count = IntRange::default();
+        if let Some(functions) = json.get("functions").and_then(serde_json::Value::as_array) {
+            for function in functions {
+                let id = function
+                    .get("function")
+                    .and_then(serde_json::Value::as_str)
+                    .map(Identifier::try_parse)
+                    .transpose()?;
+                if id.is_some_and(|e| e.is_vanilla() && e.path() == "set_count") {
+                    if let Some(value) = function.get("count") {
+                        count = IntRange::from_json(value)?;
+                    }
+                }
+            }
+        }
+
+        let ty = json
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| anyhow::anyhow!("Missing type in loot entry: {json}"))?;
+
+        match Identifier::try_parse(ty)?.path() {
+            "item" => {
+                let id = name()?;
+                let item = registry
+                    .get_from_id(&id)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown item: {id}"))?
+                    .1;
+                Ok(LootEntry::Item {
+                    item: *item.value(),
+                    count,
+                    weight,
+                })
+            }
+            "tag" => Ok(LootEntry::Tag {
+                tag: TagKey::new(registry.key.clone(), name()?),
+                count,
+                weight,
+            }),
+            "group" => Ok(LootEntry::Group {
+                children: json
+                    .get("children")
+                    .and_then(serde_json::Value::as_array)
+                    .map_or(Ok(Vec::new()), |children| {
+                        children
+                            .iter()
+                            .map(|e| Self::from_json(e, registry))
+                            .collect()
+                    })?,
+                weight,
+            }),
+            _ => Err(anyhow::anyhow!("Unsupported loot entry type: {ty}")),
+        }
+    }
+}
+
+/// A pool of loot entries rolled a random number of times.
+#[derive(Clone, PartialEq)]
+pub struct LootPool {
+    pub entries: Vec<LootEntry>,
+    pub rolls: IntRange,
+}
+
+impl LootPool {
+    /// Roll this pool, picking an entry by weights for each roll.
+    pub fn roll(&self, rng: &mut JavaRandom, registry: &Registry<Item>) -> Vec<ItemStack> {
+        let rolls = self.rolls.sample(rng);
+        let mut stacks = Vec::new();
+
+        for _ in 0..rolls {
+            if let Some(stack) =
+                pick_weighted(&self.entries, rng).and_then(|e| e.roll(rng, registry))
+            {
+                if !stack.is_empty() {
+                    stacks.push(stack);
+                }
+            }
+        }
+
+        stacks
+    }
+
+    /// Parse a pool in the vanilla loot table format.
+    pub fn from_json(json: &serde_json::Value, registry: &Registry<Item>) -> anyhow::Result<Self> {
+        Ok(Self {
+            rolls: json
+                .get("rolls")
+                .map_or(Ok(IntRange::default()), IntRange::from_json)?,
+            entries: json
+                .get("entries")
+                .and_then(serde_json::Value::as_array)
+                .map_or(Ok(Vec::new()), |entries| {
+                    entries
+                        .iter()
+                        .map(|e| LootEntry::from_json(e, registry))
+                        .collect()
+                })?,
+        })
+    }
+}
+
+/// Pick an entry by weights, or `None` if the total weight
+/// is zero or doesn't fit in an `i32` like vanilla requires.
+fn pick_weighted<'a>(entries: &'a [LootEntry], rng: &mut JavaRandom) -> Option<&'a LootEntry> {
+    let Some(total) = entries
+        .iter()
+        .try_fold(0_u32, |acc, e| acc.checked_add(e.weight()))
+        .and_then(|e| i32::try_from(e).ok())
+    else {
+        tracing::warn!("Total weight of loot entries exceeds {}", i32::MAX);
+        return None;
+    };
+    if total == 0 {
+        return None;
+    }
+
+    let mut value = rng.next_i32_bounded(total) as u32;
+    entries.iter().find(|e| {
+        if value < e.weight() {
+            true
+        } else {
+            value -= e.weight();
+            false
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> LootPool {
+        crate::registry::freeze_for_tests();
+        LootPool::from_json(
+            &serde_json::json!({
+                "rolls": { "min": 1, "max": 3 },
+                "entries": [
+                    { "type": "minecraft:item", "name": "minecraft:stone" },
+                    {
+                        "type": "minecraft:item",
+                        "name": "minecraft:apple",
+                        "weight": 3,
+                        "functions": [{
+                            "function": "minecraft:set_count",
+                            "count": { "min": 1, "max": 2 }
+                        }]
+                    }
+                ]
+            }),
+            &crate::registry::ITEM,
+        )
+        .unwrap()
+    }
+
+    fn rolled(seed: i64) -> Vec<String> {
+        pool()
+            .roll(&mut JavaRandom::new(seed), &crate::registry::ITEM)
+            .iter()
+            .map(ItemStack::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn roll_fixed_seed() {
+        assert_eq!(
+            rolled(42),
+            [
+                "1 minecraft:stone",
+                "1 minecraft:apple",
+                "2 minecraft:apple"
+            ]
+        );
+        assert_eq!(rolled(7), ["2 minecraft:apple", "1 minecraft:stone"]);
+        assert_eq!(rolled(42), rolled(42));
+    }
+
+    #[test]
+    fn weights_overflowing_i32_are_rejected() {
+        crate::registry::freeze_for_tests();
+        let item = *crate::registry::ITEM
+            .get_from_id(&Identifier::parse("minecraft:stone"))
+            .unwrap()
+            .1
+            .value();
+        let entry = |weight| LootEntry::Item {
+            item,
+            count: IntRange::default(),
+            weight,
+        };
+        let mut rng = JavaRandom::new(0);
+
+        assert!(pick_weighted(&[entry(i32::MAX as u32), entry(1)], &mut rng).is_none());
+        assert!(pick_weighted(&[entry(u32::MAX), entry(1)], &mut rng).is_none());
+        assert!(pick_weighted(&[entry(0)], &mut rng).is_none());
+        assert!(pick_weighted(&[entry(i32::MAX as u32)], &mut rng).is_some());
+    }
+
+    fn stone() -> Item {
+        crate::registry::freeze_for_tests();
+        *crate::registry::ITEM
+            .get_from_id(&Identifier::parse("minecraft:stone"))
+            .unwrap()
+            .1
+            .value()
+    }
+
+    #[test]
+    fn wide_ranges_are_rejected() {
+        let mut rng = JavaRandom::new(0);
+        let full = IntRange::new(i32::MIN, i32::MAX);
+        assert_eq!(full.sample(&mut rng), i32::MIN);
+        assert!(IntRange::new(0, i32::MAX - 1).span().is_some());
+        assert!(IntRange::new(-1, i32::MAX).span().is_none());
+
+        assert!(IntRange::from_json(&serde_json::json!({ "min": -2e9, "max": 2e9 })).is_err());
+        let range = IntRange::from_json(&serde_json::json!({ "min": -5, "max": 5 })).unwrap();
+        for _ in 0..100 {
+            assert!((-5..=5).contains(&range.sample(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn oversized_weights_fail_to_parse() {
+        stone();
+        let entry = |weight: u64| {
+            LootEntry::from_json(
+                &serde_json::json!({
+                    "type": "minecraft:item",
+                    "name": "minecraft:stone",
+                    "weight": weight
+                }),
+                &crate::registry::ITEM,
+            )
+        };
+        assert!(entry(u32::MAX as u64).is_ok_and(|e| e.weight() == u32::MAX));
+        assert!(entry(u32::MAX as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn roll_tag_entry() {
+        stone();
+        let tag = |id| TagKey::new(crate::registry::ITEM.key.clone(), Identifier::parse(id));
+        let mut rng = JavaRandom::new(0);
+
+        // bound to the lava bucket by the shared test registries
+        let entry = LootEntry::Tag {
+            tag: tag("c:unstackable"),
+            count: IntRange::constant(1),
+            weight: 1,
+        };
+        let stack = entry.roll(&mut rng, &crate::registry::ITEM).unwrap();
+        assert_eq!(stack.to_string(), "1 minecraft:lava_bucket");
+
+        let entry = LootEntry::Tag {
+            tag: tag("c:unbound"),
+            count: IntRange::constant(1),
+            weight: 1,
+        };
+        assert!(entry.roll(&mut rng, &crate::registry::ITEM).is_none());
+    }
+
+    #[test]
+    fn roll_group_entry() {
+        let apple = *crate::registry::ITEM
+            .get_from_id(&Identifier::parse("minecraft:apple"))
+            .unwrap()
+            .1
+            .value();
+        let group = LootEntry::Group {
+            children: vec![
+                LootEntry::Item {
+                    item: stone(),
+                    count: IntRange::constant(1),
+                    weight: 0,
+                },
+                LootEntry::Item {
+                    item: apple,
+                    count: IntRange::constant(3),
+                    weight: 1,
+                },
+            ],
+            weight: 1,
+        };
+        let mut rng = JavaRandom::new(0);
+        for _ in 0..10 {
+            let stack = group.roll(&mut rng, &crate::registry::ITEM).unwrap();
+            assert_eq!(stack.to_string(), "3 minecraft:apple");
+        }
+
+        let empty = LootEntry::Group {
+            children: Vec::new(),
+            weight: 1,
+        };
+        assert!(empty.roll(&mut rng, &crate::registry::ITEM).is_none());
+    }
+}
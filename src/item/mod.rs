@@ -1,4 +1,6 @@
+mod cooldown;
 mod event;
+mod loot;
 
 use std::ops::Deref;
 
@@ -7,7 +9,9 @@ use crate::{
     registry::{Registration, RegistryAccess},
 };
 
+pub use cooldown::*;
 pub use event::*;
+pub use loot::*;
 
 /// Represents an item.
 #[derive(Clone, Copy)]
@@ -20,11 +24,45 @@ pub struct Item {
 #[derive(Clone, Copy)]
 pub struct ItemDescriptor {
     pub sync_nbt: bool,
+    pub rarity: Rarity,
 }
 
 impl Default for ItemDescriptor {
     fn default() -> Self {
-        Self { sync_nbt: true }
+        Self {
+            sync_nbt: true,
+            rarity: Rarity::Common,
+        }
+    }
+}
+
+/// Rarity of an item, deciding the color of its name.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+impl Rarity {
+    /// The ARGB color of names in this rarity.
+    pub fn color(self) -> u32 {
+        match self {
+            Rarity::Common => 0xFFFFFFFF,
+            Rarity::Uncommon => 0xFFFFFF55,
+            Rarity::Rare => 0xFF55FFFF,
+            Rarity::Epic => 0xFFFF55FF,
+        }
+    }
+
+    /// The rarity of enchanted items in this rarity.
+    pub fn enchanted(self) -> Self {
+        match self {
+            Rarity::Common | Rarity::Uncommon => Rarity::Rare,
+            Rarity::Rare | Rarity::Epic => Rarity::Epic,
+        }
     }
 }
 
@@ -39,6 +77,31 @@ impl Item {
     pub fn descriptor(&self) -> &ItemDescriptor {
         &self.properties
     }
+
+    /// The fluid inside this item if this is a bucket item.
+    ///
+    /// See [`crate::fluid::register_bucket`].
+    pub fn fluid_in_bucket(&self) -> Option<crate::fluid::Fluid> {
+        crate::fluid::BUCKETS
+            .read()
+            .iter()
+            .find(|e| e.1 == self.id)
+            .and_then(|e| crate::registry::FLUID.get_from_raw(e.0))
+            .map(|e| e.deref().clone())
+    }
+
+    /// The translation key of this item, in `item.<namespace>.<path>` format.
+    pub fn translation_key(&self) -> String {
+        crate::registry::ITEM
+            .get_from_raw(self.raw_id())
+            .map_or_else(
+                || String::from("item.unregistered"),
+                |e| {
+                    let id = e.key().value();
+                    format!("item.{}.{}", id.namespace(), id.path().replace('/', "."))
+                },
+            )
+    }
 }
 
 impl Registration for Item {
@@ -80,7 +143,7 @@ impl<'de> serde::Deserialize<'de> for Item {
         Ok(crate::registry::ITEM.get_from_id(&id).map_or_else(
             || {
                 tracing::debug!("Tried to load invalid item: {id}");
-                crate::registry::ITEM.default_entry().1.as_item()
+                Self::default()
             },
             |e| *e.1.deref(),
         ))
@@ -88,8 +151,19 @@ impl<'de> serde::Deserialize<'de> for Item {
 }
 
 impl Default for Item {
+    /// The default entry of [`crate::registry::ITEM`], or the item
+    /// with raw id `0` if the registry is not frozen or not defaulted.
     fn default() -> Self {
-        *crate::registry::ITEM.default_entry().1.deref()
+        if crate::registry::ITEM.is_freezed() {
+            if let Some(e) = crate::registry::ITEM.try_default_entry() {
+                return *e.1.deref();
+            }
+        }
+
+        Self {
+            id: 0,
+            properties: ItemDescriptor::default(),
+        }
     }
 }
 
@@ -107,7 +181,7 @@ pub trait AsItem {
 impl AsItem for crate::registry::Holder<Item> {
     /// Convert this object into an item.
     fn as_item(&self) -> Item {
-        *self.deref().deref()
+        *self.value()
     }
 }
 
@@ -136,13 +210,28 @@ impl std::hash::Hash for Item {
     }
 }
 
+/// NBT keys ignored by [`ItemStack::render_key_nbt`], which change
+/// frequently but don't affect rendering, like `Damage`.
+pub static RENDER_IGNORED_NBT_KEYS: once_cell::sync::Lazy<parking_lot::RwLock<Vec<String>>> =
+    once_cell::sync::Lazy::new(|| parking_lot::RwLock::new(vec!["Damage".to_string()]));
+
 /// Represents a stack of items.
 /// This is a data container that holds the
 /// item count and the stack's NBT.
+///
+/// # Serialization
+///
+/// There are two representations of a stack:
+///
+/// - The disk form, through [`serde`], which stores the item
+///   as its string id along with `Count` and `tag`, just like
+///   the vanilla save format.
+/// - The network form, through [`Self::write_packet`] and [`Self::read_packet`]
+///   wrapping [`crate::network::Encode`] and [`crate::network::Decode`],
+///   which writes the item as its raw id and only syncs the NBT when needed.
 #[derive(Default, Clone, PartialEq)]
 pub struct ItemStack {
-    /// Count of this stack.
-    pub count: u8,
+    count: u8,
     item: Item,
     nbt: Option<crate::nbt::NbtCompound>,
 }
@@ -150,13 +239,23 @@ pub struct ItemStack {
 impl ItemStack {
     const UNBREAKABLE_KEY: &str = "Unbreakable";
     const DAMAGE_KEY: &str = "Damage";
+    const DISPLAY_KEY: &str = "display";
+    const NAME_KEY: &str = "Name";
+    const LORE_KEY: &str = "Lore";
+    const ENCHANTMENTS_KEY: &str = "Enchantments";
+    const CAN_DESTROY_KEY: &str = "CanDestroy";
+    const CAN_PLACE_ON_KEY: &str = "CanPlaceOn";
 
+    /// Creates a stack of the item, with the count
+    /// clamped to [`Self::max_count`].
     pub fn new(item: &impl AsItem, count: u8) -> Self {
-        Self {
-            count,
+        let mut stack = Self {
+            count: 0,
             item: item.as_item(),
             nbt: None,
-        }
+        };
+        stack.set_count(count);
+        stack
     }
 
     /// Whether this item stack is empty.
@@ -164,16 +263,51 @@ impl ItemStack {
         self.item == Item::default() || self.count == 0
     }
 
+    /// Count of this stack.
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    /// Set count of this stack, clamped to [`Self::max_count`].
+    pub fn set_count(&mut self, count: u8) {
+        self.count = std::cmp::min(count, self.max_count());
+    }
+
+    /// Increase count of this stack by `amount`,
+    /// without exceeding [`Self::max_count`].
+    pub fn increment(&mut self, amount: u8) {
+        self.set_count(self.count.saturating_add(amount))
+    }
+
+    /// Decrease count of this stack by `amount`,
+    /// without going below zero.
+    pub fn decrement(&mut self, amount: u8) {
+        self.count = self.count.saturating_sub(amount)
+    }
+
     /// Take amount of items from this stack into
     /// a new cloned stack with the taken amount.
     pub fn take(&mut self, amount: u8) -> Self {
         let i = std::cmp::min(amount, self.count);
         let mut stack = self.clone();
         stack.count = i;
-        self.count -= i;
+        self.decrement(i);
         stack
     }
 
+    /// Move as many items as possible from `other` into this stack
+    /// if they can be combined, and return the moved amount.
+    pub fn merge(&mut self, other: &mut Self) -> u8 {
+        if !self.can_combine(other) {
+            return 0;
+        }
+
+        let i = std::cmp::min(other.count, self.max_count().saturating_sub(self.count));
+        self.increment(i);
+        other.decrement(i);
+        i
+    }
+
     /// Take all items from this stack into a new stack.
     pub fn take_all(&mut self) -> Self {
         self.take(self.count)
@@ -253,6 +387,188 @@ impl ItemStack {
             .insert_i32(Self::DAMAGE_KEY, damage as i32);
     }
 
+    /// Returns a stack of the filled bucket of the fluid if this
+    /// is a stack of empty buckets, which holds the default fluid.
+    pub fn try_fill_bucket(&self, fluid: &crate::fluid::Fluid) -> Option<Self> {
+        let empty = crate::registry::FLUID.try_default_entry()?.1.deref();
+
+        if !self.is_empty() && self.item.fluid_in_bucket().as_ref() == Some(empty) {
+            fluid.bucket_item().map(|e| Self::new(&e, 1))
+        } else {
+            None
+        }
+    }
+
+    /// Whether this stack has a custom name in its NBT.
+    pub fn has_custom_name(&self) -> bool {
+        self.custom_name().is_some()
+    }
+
+    fn custom_name(&self) -> Option<&str> {
+        self.nbt
+            .as_ref()?
+            .get_compound(Self::DISPLAY_KEY)?
+            .get_str(Self::NAME_KEY)
+    }
+
+    /// Get the name of this stack, which comes from the
+    /// custom name in NBT, else the translated name from the
+    /// `translator`, else the id of the item, or its translation
+    /// key if the item is not registered.
+    pub fn display_name<F>(&self, translator: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(name) = self.custom_name() {
+            return text_to_plain(name);
+        }
+
+        let key = self.item.translation_key();
+        translator(&key).unwrap_or_else(|| {
+            crate::registry::ITEM
+                .get_from_raw(self.item.raw_id())
+                .map_or(key, |e| e.key().value().to_string())
+        })
+    }
+
+    /// The rarity of this stack, which is raised if the stack is enchanted.
+    pub fn rarity(&self) -> Rarity {
+        let rarity = self.item.descriptor().rarity;
+        if self
+            .nbt
+            .as_ref()
+            .and_then(|nbt| nbt.get_slice(Self::ENCHANTMENTS_KEY))
+            .is_some_and(|e| !e.is_empty())
+        {
+            rarity.enchanted()
+        } else {
+            rarity
+        }
+    }
+
+    /// Get the display name of this stack and its ARGB color, which comes
+    /// from the color of the custom name, else the rarity of this stack.
+    ///
+    /// See [`Self::display_name`].
+    pub fn formatted_name<F>(&self, translator: F) -> (String, u32)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        (
+            self.display_name(translator),
+            self.custom_name()
+                .and_then(text_color)
+                .unwrap_or_else(|| self.rarity().color()),
+        )
+    }
+
+    /// Lore lines of this stack in plain text, parsed from
+    /// the JSON text components in `display.Lore`.
+    pub fn lore(&self) -> Vec<String> {
+        self.nbt
+            .as_ref()
+            .and_then(|nbt| nbt.get_compound(Self::DISPLAY_KEY))
+            .and_then(|display| display.get_slice(Self::LORE_KEY))
+            .map_or_else(Vec::new, |lore| {
+                lore.iter()
+                    .filter_map(|e| match e {
+                        crate::nbt::NbtElement::String(value) => Some(text_to_plain(value)),
+                        _ => None,
+                    })
+                    .collect()
+            })
+    }
+
+    /// Replace lore lines of this stack with the plain text lines.
+    pub fn set_lore(&mut self, lines: &[&str]) {
+        let lines = lines.iter().map(|e| plain_to_text(e)).collect::<Vec<_>>();
+        self.display_mut().insert_str_list(
+            Self::LORE_KEY,
+            &lines.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+    }
+
+    /// Append a plain text line to the lore of this stack.
+    pub fn add_lore_line(&mut self, line: &str) {
+        let display = self.display_mut();
+        match display.get_mut(Self::LORE_KEY) {
+            Some(crate::nbt::NbtElement::List(lore)) => {
+                lore.push(crate::nbt::NbtElement::String(plain_to_text(line)))
+            }
+            _ => display.insert_str_list(Self::LORE_KEY, &[&plain_to_text(line)]),
+        }
+    }
+
+    /// Get the `display` compound, creating it if absent.
+    fn display_mut(&mut self) -> &mut crate::nbt::NbtCompound {
+        let display = self
+            .get_or_init_nbt()
+            .entry(Self::DISPLAY_KEY.to_string())
+            .or_insert_with(|| crate::nbt::NbtElement::Compound(crate::nbt::NbtCompound::new()));
+
+        if !matches!(display, crate::nbt::NbtElement::Compound(_)) {
+            *display = crate::nbt::NbtElement::Compound(crate::nbt::NbtCompound::new());
+        }
+
+        match display {
+            crate::nbt::NbtElement::Compound(display) => display,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Whether this stack can destroy the target block in adventure mode.
+    ///
+    /// A missing or empty `CanDestroy` list means unrestricted.
+    pub fn can_destroy(&self, block_id: &Identifier) -> bool {
+        self.adventure_predicate(Self::CAN_DESTROY_KEY, block_id)
+    }
+
+    /// Whether this stack can be placed on the target block in adventure mode.
+    ///
+    /// A missing or empty `CanPlaceOn` list means unrestricted.
+    pub fn can_place_on(&self, block_id: &Identifier) -> bool {
+        self.adventure_predicate(Self::CAN_PLACE_ON_KEY, block_id)
+    }
+
+    /// Set blocks that this stack can destroy, in
+    /// block ids or block tag ids with a `#` prefix.
+    pub fn set_can_destroy(&mut self, blocks: &[&str]) {
+        self.get_or_init_nbt()
+            .insert_str_list(Self::CAN_DESTROY_KEY, blocks)
+    }
+
+    /// Set blocks that this stack can be placed on, in
+    /// block ids or block tag ids with a `#` prefix.
+    pub fn set_can_place_on(&mut self, blocks: &[&str]) {
+        self.get_or_init_nbt()
+            .insert_str_list(Self::CAN_PLACE_ON_KEY, blocks)
+    }
+
+    fn adventure_predicate(&self, key: &str, block_id: &Identifier) -> bool {
+        let list = match self.nbt.as_ref().and_then(|nbt| nbt.get_slice(key)) {
+            Some(list) if !list.is_empty() => list,
+            _ => return true,
+        };
+
+        list.iter().any(|e| {
+            let crate::nbt::NbtElement::String(value) = e else {
+                return false;
+            };
+
+            if let Some(tag) = value.strip_prefix('#') {
+                Identifier::try_parse(tag).is_ok_and(|tag| {
+                    let tag =
+                        crate::registry::tag::TagKey::new(crate::registry::BLOCK.key.clone(), tag);
+                    crate::registry::BLOCK
+                        .get_from_id(block_id)
+                        .is_some_and(|e| e.1.is_in(&tag))
+                })
+            } else {
+                Identifier::try_parse(value).is_ok_and(|e| &e == block_id)
+            }
+        })
+    }
+
     /// Whether the given item stack's items and NBT are equal with this stack.
     pub fn can_combine(&self, other: &Self) -> bool {
         if self.item() != other.item() {
@@ -263,6 +579,64 @@ impl ItemStack {
             self.nbt == other.nbt
         }
     }
+
+    /// Clone of the NBT with keys in [`RENDER_IGNORED_NBT_KEYS`] removed,
+    /// for keying rendering caches.
+    ///
+    /// Returns `None` if nothing is left after removing the keys.
+    pub fn render_key_nbt(&self) -> Option<crate::nbt::NbtCompound> {
+        let mut nbt = self.nbt.clone()?;
+        for key in RENDER_IGNORED_NBT_KEYS.read().iter() {
+            nbt.remove(key);
+        }
+
+        if nbt.is_empty() {
+            None
+        } else {
+            Some(nbt)
+        }
+    }
+
+    /// Validate this stack through validators registered in [`EVENTS`],
+    /// returning problems like missing or malformed nbt keys.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        EVENTS.read().validate(self)
+    }
+
+    /// Whether the given item stack is the same tool as this stack,
+    /// comparing items and NBT except the damage.
+    ///
+    /// A missing NBT equals to an empty one.
+    pub fn is_same_tool(&self, other: &Self) -> bool {
+        let without_damage = |stack: &Self| {
+            stack.nbt.as_ref().map_or(0, |nbt| {
+                nbt.len() - nbt.contains_key(Self::DAMAGE_KEY) as usize
+            })
+        };
+
+        self.item() == other.item()
+            && without_damage(self) == without_damage(other)
+            && self.nbt.iter().flatten().all(|(key, value)| {
+                key == Self::DAMAGE_KEY
+                    || other.nbt.as_ref().and_then(|nbt| nbt.get(key)) == Some(value)
+            })
+    }
+
+    /// Write this stack in the network form, see [`crate::network::Encode`].
+    pub fn write_packet<B>(&self, buf: &mut B) -> anyhow::Result<()>
+    where
+        B: bytes::BufMut,
+    {
+        crate::network::Encode::encode(self, buf)
+    }
+
+    /// Read a stack in the network form, see [`crate::network::Decode`].
+    pub fn read_packet<B>(buf: &mut B) -> anyhow::Result<Self>
+    where
+        B: bytes::Buf,
+    {
+        <Self as crate::network::Decode>::decode(buf)
+    }
 }
 
 impl serde::Serialize for ItemStack {
@@ -272,6 +646,8 @@ impl serde::Serialize for ItemStack {
     {
         RawItemStack {
             id: self.item,
+            // bit-preserving cast, counts above 127 are stored
+            // as negative bytes and restored when deserializing
             count: self.count as i8,
             tag: self.nbt.clone(),
         }
@@ -290,6 +666,7 @@ impl<'de> serde::Deserialize<'de> for ItemStack {
             EVENTS.read().post_process_nbt(item, nbt);
         }
         let mut stack = Self {
+            // restores counts above 127 from negative bytes
             count: raw.count as u8,
             item: raw.id,
             nbt: raw.tag,
@@ -315,11 +692,408 @@ impl std::fmt::Display for ItemStack {
     }
 }
 
+/// Insert the stack into the slots and return the remainder,
+/// which is empty if the whole stack is inserted.
+///
+/// Compatible non-empty slots are topped up first, then
+/// the rest is placed into the first empty slots.
+pub fn insert_into(slots: &mut [ItemStack], mut stack: ItemStack) -> ItemStack {
+    for slot in slots.iter_mut().filter(|e| !e.is_empty()) {
+        if stack.is_empty() {
+            return stack;
+        }
+        slot.merge(&mut stack);
+    }
+
+    for slot in slots.iter_mut().filter(|e| e.is_empty()) {
+        if stack.is_empty() {
+            break;
+        }
+        *slot = stack.take(stack.max_count());
+    }
+
+    stack
+}
+
+/// Convert a JSON text component into plain text.
+/// Non-JSON strings are returned as-is.
+fn text_to_plain(json: &str) -> String {
+    fn append(value: &serde_json::Value, string: &mut String) {
+        match value {
+            serde_json::Value::String(s) => string.push_str(s),
+            serde_json::Value::Array(values) => values.iter().for_each(|e| append(e, string)),
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(text)) = map.get("text") {
+                    string.push_str(text);
+                }
+
+                if let Some(serde_json::Value::Array(extra)) = map.get("extra") {
+                    extra.iter().for_each(|e| append(e, string));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(value) => {
+            let mut string = String::new();
+            append(&value, &mut string);
+            string
+        }
+        Err(_) => json.to_string(),
+    }
+}
+
+/// Get the ARGB color of a JSON text component, which can be
+/// a formatting color name or a `#RRGGBB` hex color.
+fn text_color(json: &str) -> Option<u32> {
+    let value = serde_json::from_str::<serde_json::Value>(json).ok()?;
+    let color = value.get("color")?.as_str()?;
+
+    let rgb = if let Some(hex) = color.strip_prefix('#') {
+        u32::from_str_radix(hex, 16).ok()?
+    } else {
+        match color {
+            "black" => 0x000000,
+            "dark_blue" => 0x0000AA,
+            "dark_green" => 0x00AA00,
+            "dark_aqua" => 0x00AAAA,
+            "dark_red" => 0xAA0000,
+            "dark_purple" => 0xAA00AA,
+            "gold" => 0xFFAA00,
+            "gray" => 0xAAAAAA,
+            "dark_gray" => 0x555555,
+            "blue" => 0x5555FF,
+            "green" => 0x55FF55,
+            "aqua" => 0x55FFFF,
+            "red" => 0xFF5555,
+            "light_purple" => 0xFF55FF,
+            "yellow" => 0xFFFF55,
+            "white" => 0xFFFFFF,
+            _ => return None,
+        }
+    };
+
+    Some(0xFF000000 | (rgb & 0xFFFFFF))
+}
+
+/// Convert plain text into a JSON text component.
+fn plain_to_text(text: &str) -> String {
+    serde_json::json!({ "text": text }).to_string()
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct RawItemStack {
     id: Item,
+    /// Count of the stack, stored as a byte like vanilla.
+    ///
+    /// The full `u8` range of counts is kept through a bit-preserving
+    /// `as` cast in both directions.
     #[serde(rename = "Count")]
     count: i8,
     #[serde(default)]
     tag: Option<crate::nbt::NbtCompound>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str) -> Item {
+        crate::registry::freeze_for_tests();
+        *crate::registry::ITEM
+            .get_from_id(&Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap())
+            .unwrap()
+            .1
+            .value()
+    }
+
+    #[test]
+    fn count_arithmetic_saturates() {
+        let mut stack = ItemStack::new(&item("stone"), 60);
+        stack.increment(255);
+        assert_eq!(stack.count(), 64);
+        stack.set_count(255);
+        assert_eq!(stack.count(), 64);
+
+        stack.decrement(255);
+        assert_eq!(stack.count(), 0);
+        assert!(stack.is_empty());
+
+        let mut stack = ItemStack::new(&item("stone"), 10);
+        let taken = stack.take(255);
+        assert_eq!((taken.count(), stack.count()), (10, 0));
+    }
+
+    #[test]
+    fn new_clamps_to_max_count() {
+        assert_eq!(ItemStack::new(&item("stone"), 200).count(), 64);
+        assert_eq!(ItemStack::new(&item("stone"), 64).count(), 64);
+    }
+
+    #[test]
+    fn merge_respects_max_count() {
+        let mut a = ItemStack::new(&item("stone"), 40);
+        let mut b = ItemStack::new(&item("stone"), 64);
+        assert_eq!(a.merge(&mut b), 24);
+        assert_eq!((a.count(), b.count()), (64, 40));
+
+        let mut c = ItemStack::new(&item("apple"), 1);
+        assert_eq!(c.merge(&mut b), 0);
+        assert_eq!(c.count(), 1);
+    }
+
+    #[test]
+    fn disk_form() {
+        let mut stack = ItemStack::new(&item("apple"), 1);
+        // above any max count here, to cover counts stored as negative bytes
+        stack.count = 200;
+        stack.get_or_init_nbt().insert_str("Note", "disk");
+
+        let nbt = crate::nbt::to_nbt(&stack).unwrap();
+        let crate::nbt::NbtElement::Compound(compound) = &nbt else {
+            panic!("stack should serialize to a compound");
+        };
+        assert_eq!(compound.get_str("id"), Some("minecraft:apple"));
+        assert_eq!(compound.get_i8("Count"), Some(200_u8 as i8));
+        assert_eq!(
+            compound.get_compound("tag").and_then(|e| e.get_str("Note")),
+            Some("disk")
+        );
+
+        let read: ItemStack = crate::nbt::from_nbt(&nbt).unwrap();
+        assert!(read == stack);
+    }
+
+    #[test]
+    fn wire_form() {
+        let stone = item("stone");
+        let mut buf = Vec::new();
+        ItemStack::new(&stone, 3).write_packet(&mut buf).unwrap();
+        // present flag, raw id, count and an end tag for no nbt
+        assert_eq!(buf, [1, stone.raw_id() as u8, 3, 0]);
+        assert!(ItemStack::read_packet(&mut buf.as_slice()).unwrap() == ItemStack::new(&stone, 3));
+
+        let mut stack = ItemStack::new(&item("apple"), 1);
+        stack.get_or_init_nbt().insert_str("Note", "wire");
+        let mut buf = Vec::new();
+        stack.write_packet(&mut buf).unwrap();
+        let mut slice = buf.as_slice();
+        assert!(ItemStack::read_packet(&mut slice).unwrap() == stack);
+        assert!(slice.is_empty());
+
+        let mut buf = Vec::new();
+        ItemStack::default().write_packet(&mut buf).unwrap();
+        assert_eq!(buf, [0]);
+        assert!(ItemStack::read_packet(&mut buf.as_slice())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn render_key_ignores_damage() {
+        let item = Item::new(ItemDescriptor::default());
+        let mut a = ItemStack::new(&item, 1);
+        a.get_or_init_nbt().insert_i32("Damage", 1);
+        a.get_or_init_nbt().insert_str("Color", "red");
+        let mut b = a.clone();
+        b.get_or_init_nbt().insert_i32("Damage", 20);
+
+        assert!(a != b);
+        assert_eq!(a.render_key_nbt(), b.render_key_nbt());
+        assert!(!a.render_key_nbt().unwrap().contains_key("Damage"));
+
+        b.get_or_init_nbt().insert_str("Color", "blue");
+        assert_ne!(a.render_key_nbt(), b.render_key_nbt());
+
+        let mut damaged = ItemStack::new(&item, 1);
+        damaged.get_or_init_nbt().insert_i32("Damage", 3);
+        assert_eq!(damaged.render_key_nbt(), None);
+    }
+
+    #[test]
+    fn water_bucket_round_trip() {
+        let fluid = |path| {
+            crate::registry::FLUID
+                .get_from_id(&Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap())
+                .unwrap()
+                .1
+                .deref()
+                .clone()
+        };
+        item("air");
+        crate::fluid::register_vanilla_buckets();
+
+        let water = fluid("water");
+        assert!(water.bucket_item() == Some(item("water_bucket")));
+        assert!(item("water_bucket").fluid_in_bucket() == Some(water.clone()));
+        assert!(item("bucket").fluid_in_bucket() == Some(fluid("empty")));
+        assert!(item("stone").fluid_in_bucket().is_none());
+
+        let filled = ItemStack::new(&item("bucket"), 3)
+            .try_fill_bucket(&water)
+            .unwrap();
+        assert!(filled.item() == item("water_bucket"));
+        assert_eq!(filled.count(), 1);
+        assert!(filled.item().fluid_in_bucket() == Some(water.clone()));
+
+        assert!(filled.try_fill_bucket(&fluid("lava")).is_none());
+        assert!(ItemStack::new(&item("stone"), 1)
+            .try_fill_bucket(&water)
+            .is_none());
+    }
+
+    #[test]
+    fn same_tool_ignores_damage() {
+        let enchantment = |id: &str| {
+            let mut nbt = crate::nbt::NbtCompound::new();
+            nbt.insert_str("id", id);
+            nbt.insert_i16("lvl", 1);
+            vec![crate::nbt::NbtElement::Compound(nbt)]
+        };
+
+        let mut a = ItemStack::new(&item("stone"), 1);
+        a.set_damage(1);
+        a.get_or_init_nbt()
+            .insert_list("Enchantments", enchantment("minecraft:efficiency"));
+        let mut b = a.clone();
+        b.set_damage(20);
+
+        assert!(a != b);
+        assert!(a.is_same_tool(&b));
+        assert!(b.is_same_tool(&a));
+
+        b.get_or_init_nbt()
+            .insert_list("Enchantments", enchantment("minecraft:unbreaking"));
+        assert!(!a.is_same_tool(&b));
+        assert!(!b.is_same_tool(&a));
+
+        let mut damaged = ItemStack::new(&item("stone"), 1);
+        damaged.set_damage(5);
+        assert!(damaged.is_same_tool(&ItemStack::new(&item("stone"), 1)));
+        assert!(!damaged.is_same_tool(&ItemStack::new(&item("apple"), 1)));
+    }
+
+    #[test]
+    fn insert_tops_up_before_empty_slots() {
+        let counts = |slots: &[ItemStack]| {
+            slots
+                .iter()
+                .map(|e| (e.item().raw_id(), e.count()))
+                .collect::<Vec<_>>()
+        };
+        let mut slots = [
+            ItemStack::new(&item("stone"), 60),
+            ItemStack::default(),
+            ItemStack::new(&item("apple"), 10),
+            ItemStack::new(&item("stone"), 63),
+        ];
+
+        let remainder = insert_into(&mut slots, ItemStack::new(&item("stone"), 64));
+        assert!(remainder.is_empty());
+        assert_eq!(
+            counts(&slots),
+            [
+                (item("stone").raw_id(), 64),
+                (item("stone").raw_id(), 59),
+                (item("apple").raw_id(), 10),
+                (item("stone").raw_id(), 64),
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_into_full_inventory_returns_remainder() {
+        let mut slots = [
+            ItemStack::new(&item("stone"), 62),
+            ItemStack::new(&item("apple"), 64),
+        ];
+
+        let remainder = insert_into(&mut slots, ItemStack::new(&item("stone"), 10));
+        assert!(remainder.item() == item("stone"));
+        assert_eq!(remainder.count(), 8);
+        assert_eq!(slots[0].count(), 64);
+        assert_eq!(slots[1].count(), 64);
+
+        let remainder = insert_into(&mut [], ItemStack::new(&item("apple"), 3));
+        assert_eq!(remainder.count(), 3);
+    }
+
+    #[test]
+    fn display_name_prefers_custom_name() {
+        let mut stack = ItemStack::new(&item("apple"), 1);
+        stack
+            .display_mut()
+            .insert_str(ItemStack::NAME_KEY, r#"{"text":"Golden"}"#);
+        assert_eq!(stack.display_name(|_| Some("Apple".to_string())), "Golden");
+    }
+
+    #[test]
+    fn display_name_falls_back_to_translation() {
+        let stack = ItemStack::new(&item("apple"), 1);
+        assert_eq!(
+            stack.display_name(|key| (key == "item.minecraft.apple").then(|| "Apple".to_string())),
+            "Apple"
+        );
+    }
+
+    #[test]
+    fn display_name_falls_back_to_registry_id() {
+        let stack = ItemStack::new(&item("apple"), 1);
+        assert_eq!(stack.display_name(|_| None), "minecraft:apple");
+    }
+
+    #[test]
+    fn adventure_predicates() {
+        let stone = Identifier::new(crate::util::VANILLA_NAMESPACE, "stone").unwrap();
+        let glass = Identifier::new(crate::util::VANILLA_NAMESPACE, "glass").unwrap();
+        let mut stack = ItemStack::new(&item("apple"), 1);
+
+        assert!(stack.can_destroy(&stone) && stack.can_place_on(&glass));
+
+        stack.set_can_destroy(&[]);
+        assert!(stack.can_destroy(&stone));
+
+        stack.set_can_destroy(&["stone"]);
+        assert!(stack.can_destroy(&stone));
+        assert!(!stack.can_destroy(&glass));
+
+        stack.set_can_place_on(&["#minecraft:impermeable"]);
+        assert!(stack.can_place_on(&glass));
+        assert!(!stack.can_place_on(&stone));
+    }
+
+    #[test]
+    fn lore_lines_round_trip() {
+        let mut stack = ItemStack::new(&item("apple"), 1);
+        assert!(stack.lore().is_empty());
+
+        stack.add_lore_line("created \"display\"");
+        assert_eq!(stack.lore(), ["created \"display\""]);
+
+        stack.set_lore(&["first", "second"]);
+        stack.add_lore_line("third");
+        assert_eq!(stack.lore(), ["first", "second", "third"]);
+    }
+
+    #[test]
+    fn lore_replaces_malformed_display() {
+        let mut stack = ItemStack::new(&item("apple"), 1);
+        stack
+            .get_or_init_nbt()
+            .insert_i32(ItemStack::DISPLAY_KEY, 1);
+        assert!(stack.lore().is_empty());
+
+        stack.add_lore_line("line");
+        assert_eq!(stack.lore(), ["line"]);
+    }
+
+    #[test]
+    fn holder_as_item_is_registered_item() {
+        let apple = item("apple");
+        let holder = crate::registry::ITEM.get_from_raw(apple.raw_id()).unwrap();
+        assert!(holder.as_item() == apple);
+        assert!(holder.as_item() != item("stone"));
+    }
+}
@@ -17,7 +17,7 @@ pub use util::*;
 /// Core utils of Rimecraft.
 pub mod prelude {
     pub use crate::{
-        nbt::NbtCompoundExt,
-        util::{math::BlockPos, EnumValues, Identifier},
+        nbt::{NbtCompoundExt, NbtElementExt, NbtTypeExt},
+        util::{math::BlockPos, EnumValues, Identifier, InternedId},
     };
 }
@@ -1,10 +1,7 @@
 pub use fastnbt_rc::Tag as NbtType;
 pub use fastnbt_rc::Value as NbtElement;
 
-pub use fastnbt_rc::{
-    from_bytes, from_bytes_with_opts, from_reader, nbt, to_bytes, to_writer, ByteArray, DeOpts,
-    IntArray, LongArray,
-};
+pub use fastnbt_rc::{nbt, ByteArray, IntArray, LongArray};
 
 pub use fastnbt_rc::value::from_value as from_nbt;
 pub use fastnbt_rc::value::to_value as to_nbt;
@@ -17,7 +14,157 @@ pub type NbtCompound = std::collections::HashMap<String, NbtElement>;
 
 pub type NbtList = Vec<NbtElement>;
 
-/// Some extensions for [`NbtCompound`].
+/// Errors of nbt operations.
+#[derive(Debug)]
+pub enum NbtError {
+    /// The input ended before an element was fully read.
+    UnexpectedEnd,
+    /// An unknown type id.
+    InvalidType(u8),
+    /// Elements are nested too deeply.
+    DepthLimit,
+    /// A size or entry limit has been reached.
+    SizeLimit,
+    /// A string which is not valid (modified) utf-8, with its raw bytes.
+    Utf8(Vec<u8>),
+    /// A list containing elements of different types.
+    HeterogeneousList,
+    /// An element of a type other than the existing one.
+    TypeMismatch {
+        expected: NbtType,
+        found: NbtType,
+    },
+    Io(std::io::Error),
+}
+
+impl NbtError {
+    /// Convert this error into an [`std::io::Error`] for compatibility.
+    pub fn into_io_error(self) -> std::io::Error {
+        match self {
+            NbtError::Io(err) => err,
+            NbtError::UnexpectedEnd => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, self),
+            _ => std::io::Error::new(std::io::ErrorKind::InvalidData, self),
+        }
+    }
+}
+
+impl std::fmt::Display for NbtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NbtError::UnexpectedEnd => f.write_str("Unexpected end of nbt input"),
+            NbtError::InvalidType(id) => write!(f, "Invalid nbt type id: {id}"),
+            NbtError::DepthLimit => f.write_str("Nbt depth limit exceeded"),
+            NbtError::SizeLimit => f.write_str("Nbt size limit exceeded"),
+            NbtError::Utf8(bytes) => write!(f, "Non-unicode string: {bytes:?}"),
+            NbtError::HeterogeneousList => f.write_str("Nbt list with mixed element types"),
+            NbtError::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Nbt type mismatch: expected {expected:?}, found {found:?}"
+                )
+            }
+            NbtError::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for NbtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NbtError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for NbtError {
+    fn from(value: std::io::Error) -> Self {
+        if value.kind() == std::io::ErrorKind::UnexpectedEof {
+            Self::UnexpectedEnd
+        } else {
+            Self::Io(value)
+        }
+    }
+}
+
+/// A [`NbtCompound`] with an optional cap on its entry count,
+/// for guarding memory when filling compounds from untrusted sources.
+///
+/// The compound can be read through [`std::ops::Deref`], but
+/// can only be mutated through [`Self::put`] to respect the cap.
+#[derive(Clone, Default)]
+pub struct LimitedNbtCompound {
+    compound: NbtCompound,
+    limit: Option<usize>,
+}
+
+impl LimitedNbtCompound {
+    /// Creates an empty compound without entry cap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty compound which can hold at most `limit` entries.
+    pub fn with_capacity_limit(limit: usize) -> Self {
+        Self {
+            compound: NbtCompound::new(),
+            limit: Some(limit),
+        }
+    }
+
+    /// Insert an element into this compound and return the previous one.
+    ///
+    /// Replacing an existing key is always allowed, while inserting a new
+    /// key fails if the entry cap has been reached.
+    pub fn put(&mut self, key: &str, element: NbtElement) -> Result<Option<NbtElement>, NbtError> {
+        if let Some(limit) = self.limit {
+            if !self.compound.contains_key(key) && self.compound.len() >= limit {
+                return Err(NbtError::SizeLimit);
+            }
+        }
+
+        Ok(self.compound.insert(key.to_string(), element))
+    }
+
+    /// Remove an element from this compound.
+    pub fn remove(&mut self, key: &str) -> Option<NbtElement> {
+        self.compound.remove(key)
+    }
+
+    /// The entry cap of this compound, or `None` if unlimited.
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    pub fn into_inner(self) -> NbtCompound {
+        self.compound
+    }
+}
+
+impl std::ops::Deref for LimitedNbtCompound {
+    type Target = NbtCompound;
+
+    fn deref(&self) -> &Self::Target {
+        &self.compound
+    }
+}
+
+/// Some extensions for [`NbtCompound`], carrying typed getters and inserters
+/// so the compound itself stays a plain map. This is exported in the prelude.
+///
+/// Getters only accept elements of their exact types, and [`Self::get_bool`]
+/// reads a byte as non-zero. The `_lenient` numeric getters like
+/// [`Self::get_i32_lenient`] coerce any numeric element the same as vanilla.
+///
+/// ```
+/// use rimecraft::{nbt::NbtCompound, prelude::*};
+///
+/// let mut nbt = NbtCompound::new();
+/// nbt.insert_i32("Count", 3);
+/// assert_eq!(nbt.get_i32("Count"), Some(3));
+/// assert_eq!(nbt.get_i64("Count"), None);
+/// assert_eq!(nbt.get_i64_lenient("Count"), Some(3));
+/// ```
 pub trait NbtCompoundExt {
     fn insert_i8(&mut self, key: &str, value: i8);
     fn insert_i16(&mut self, key: &str, value: i16);
@@ -29,9 +176,23 @@ pub trait NbtCompoundExt {
     fn insert_i8_slice(&mut self, key: &str, value: &[i8]);
     fn insert_i32_slice(&mut self, key: &str, value: &[i32]);
     fn insert_i64_slice(&mut self, key: &str, value: &[i64]);
+    fn insert_i32_list(&mut self, key: &str, value: &[i32]);
+    fn insert_i64_list(&mut self, key: &str, value: &[i64]);
+    fn insert_str_list(&mut self, key: &str, value: &[&str]);
+    fn insert_compound_list(&mut self, key: &str, value: Vec<NbtCompound>);
+    fn insert_compound(&mut self, key: &str, value: NbtCompound);
+    /// Insert a list of elements, which should be of the same type.
+    fn insert_list(&mut self, key: &str, value: NbtList);
     fn insert_bool(&mut self, key: &str, value: bool) {
         self.insert_i8(key, if value { 1 } else { 0 })
     }
+    /// Insert an element and return the previous one, failing with
+    /// [`NbtError::TypeMismatch`] if the key holds an element of another type.
+    fn put_checked(
+        &mut self,
+        key: &str,
+        element: NbtElement,
+    ) -> Result<Option<NbtElement>, NbtError>;
 
     fn get_type(&self, key: &str) -> Option<NbtType>;
     fn contains(&self, key: &str, nbt_type: NbtType) -> bool {
@@ -44,18 +205,71 @@ pub trait NbtCompoundExt {
     fn get_i64(&self, key: &str) -> Option<i64>;
     fn get_f32(&self, key: &str) -> Option<f32>;
     fn get_f64(&self, key: &str) -> Option<f64>;
+    /// Get a byte, coercing other numeric elements like vanilla
+    /// by flooring floats and wrapping integers.
+    ///
+    /// This applies to all `_lenient` getters.
+    fn get_i8_lenient(&self, key: &str) -> Option<i8>;
+    fn get_i16_lenient(&self, key: &str) -> Option<i16>;
+    fn get_i32_lenient(&self, key: &str) -> Option<i32>;
+    fn get_i64_lenient(&self, key: &str) -> Option<i64>;
+    fn get_f32_lenient(&self, key: &str) -> Option<f32>;
+    fn get_f64_lenient(&self, key: &str) -> Option<f64>;
     fn get_str(&self, key: &str) -> Option<&str>;
     fn get_i8_slice(&self, key: &str) -> Option<&[i8]>;
     fn get_i32_slice(&self, key: &str) -> Option<&[i32]>;
     fn get_i64_slice(&self, key: &str) -> Option<&[i64]>;
     fn get_compound(&self, key: &str) -> Option<&NbtCompound>;
     fn get_slice(&self, key: &str) -> Option<&[NbtElement]>;
+    /// Get a list of ints, or `None` if the list contains other types.
+    ///
+    /// A list stores every int as a separate [`NbtElement`], so the
+    /// values can't be borrowed as `&[i32]` and are collected instead.
+    /// Use [`Self::get_i32_slice`] to borrow the ints of an int array.
+    fn get_i32_list(&self, key: &str) -> Option<Vec<i32>>;
     fn get_bool(&self, key: &str) -> Option<bool> {
         self.get_i8(key).map(|e| e != 0)
     }
+    /// Get any numeric element widened to `f64`,
+    /// or `None` if the element is not numeric.
+    fn get_number(&self, key: &str) -> Option<f64>;
+    fn get_number_or(&self, key: &str, default: f64) -> f64 {
+        self.get_number(key).unwrap_or(default)
+    }
+
+    /// Feed this compound into the given hasher, independent
+    /// from the iteration order of entries.
+    ///
+    /// Equal compounds always produce equal hashes.
+    fn hash_stable<H: std::hash::Hasher>(&self, state: &mut H);
+
+    /// Deep merge the other compound into this compound, where compounds
+    /// under the same key are merged recursively and other elements
+    /// from the other compound replace existing ones.
+    fn copy_from(&mut self, other: &NbtCompound);
+
+    /// Get all keys starting with the given prefix, in no particular order.
+    ///
+    /// Bulk removal is provided by [`NbtCompound::retain`] and [`NbtCompound::clear`].
+    fn keys_matching(&self, prefix: &str) -> Vec<&str>;
 }
 
 impl NbtCompoundExt for NbtCompound {
+    fn put_checked(
+        &mut self,
+        key: &str,
+        element: NbtElement,
+    ) -> Result<Option<NbtElement>, NbtError> {
+        if let Some(expected) = self.get_type(key) {
+            let found = element.get_nbt_type();
+            if expected != found {
+                return Err(NbtError::TypeMismatch { expected, found });
+            }
+        }
+
+        Ok(self.insert(key.to_string(), element))
+    }
+
     fn insert_i8(&mut self, key: &str, value: i8) {
         self.insert(key.to_string(), NbtElement::Byte(value));
     }
@@ -105,21 +319,49 @@ impl NbtCompoundExt for NbtCompound {
         );
     }
 
+    fn insert_i32_list(&mut self, key: &str, value: &[i32]) {
+        self.insert(
+            key.to_string(),
+            NbtElement::List(value.iter().map(|e| NbtElement::Int(*e)).collect()),
+        );
+    }
+
+    fn insert_i64_list(&mut self, key: &str, value: &[i64]) {
+        self.insert(
+            key.to_string(),
+            NbtElement::List(value.iter().map(|e| NbtElement::Long(*e)).collect()),
+        );
+    }
+
+    fn insert_str_list(&mut self, key: &str, value: &[&str]) {
+        self.insert(
+            key.to_string(),
+            NbtElement::List(
+                value
+                    .iter()
+                    .map(|e| NbtElement::String(e.to_string()))
+                    .collect(),
+            ),
+        );
+    }
+
+    fn insert_compound_list(&mut self, key: &str, value: Vec<NbtCompound>) {
+        self.insert(
+            key.to_string(),
+            NbtElement::List(value.into_iter().map(NbtElement::Compound).collect()),
+        );
+    }
+
+    fn insert_compound(&mut self, key: &str, value: NbtCompound) {
+        self.insert(key.to_string(), NbtElement::Compound(value));
+    }
+
+    fn insert_list(&mut self, key: &str, value: NbtList) {
+        self.insert(key.to_string(), NbtElement::List(value));
+    }
+
     fn get_type(&self, key: &str) -> Option<NbtType> {
-        self.get(key).map(|e| match e {
-            NbtElement::Byte(_) => NbtType::Byte,
-            NbtElement::Short(_) => NbtType::Short,
-            NbtElement::Int(_) => NbtType::Int,
-            NbtElement::Long(_) => NbtType::Long,
-            NbtElement::Float(_) => NbtType::Float,
-            NbtElement::Double(_) => NbtType::Double,
-            NbtElement::String(_) => NbtType::String,
-            NbtElement::ByteArray(_) => NbtType::ByteArray,
-            NbtElement::IntArray(_) => NbtType::IntArray,
-            NbtElement::LongArray(_) => NbtType::LongArray,
-            NbtElement::List(_) => NbtType::List,
-            NbtElement::Compound(_) => NbtType::Compound,
-        })
+        self.get(key).map(NbtElementExt::get_nbt_type)
     }
 
     fn get_i8(&self, key: &str) -> Option<i8> {
@@ -176,6 +418,48 @@ impl NbtCompoundExt for NbtCompound {
             .flatten()
     }
 
+    fn get_i8_lenient(&self, key: &str) -> Option<i8> {
+        number_of(self.get(key)?).map(|e| match e {
+            Ok(v) => v as i8,
+            Err(v) => v.floor() as i32 as i8,
+        })
+    }
+
+    fn get_i16_lenient(&self, key: &str) -> Option<i16> {
+        number_of(self.get(key)?).map(|e| match e {
+            Ok(v) => v as i16,
+            Err(v) => v.floor() as i32 as i16,
+        })
+    }
+
+    fn get_i32_lenient(&self, key: &str) -> Option<i32> {
+        number_of(self.get(key)?).map(|e| match e {
+            Ok(v) => v as i32,
+            Err(v) => v.floor() as i32,
+        })
+    }
+
+    fn get_i64_lenient(&self, key: &str) -> Option<i64> {
+        number_of(self.get(key)?).map(|e| match e {
+            Ok(v) => v,
+            Err(v) => v.floor() as i64,
+        })
+    }
+
+    fn get_f32_lenient(&self, key: &str) -> Option<f32> {
+        number_of(self.get(key)?).map(|e| match e {
+            Ok(v) => v as f32,
+            Err(v) => v as f32,
+        })
+    }
+
+    fn get_f64_lenient(&self, key: &str) -> Option<f64> {
+        number_of(self.get(key)?).map(|e| match e {
+            Ok(v) => v as f64,
+            Err(v) => v,
+        })
+    }
+
     fn get_str(&self, key: &str) -> Option<&str> {
         self.get(key)
             .map(|e| match e {
@@ -229,84 +513,2145 @@ impl NbtCompoundExt for NbtCompound {
             })
             .flatten()
     }
+
+    fn get_i32_list(&self, key: &str) -> Option<Vec<i32>> {
+        self.get_slice(key)?
+            .iter()
+            .map(|e| match e {
+                NbtElement::Int(value) => Some(*value),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn hash_stable<H: std::hash::Hasher>(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        state.write_usize(entries.len());
+        for (key, value) in entries {
+            key.hash(state);
+            value.hash_stable(state);
+        }
+    }
+
+    fn get_number(&self, key: &str) -> Option<f64> {
+        self.get_f64_lenient(key)
+    }
+
+    fn copy_from(&mut self, other: &NbtCompound) {
+        for (key, value) in other.iter() {
+            match (self.get_mut(key), value) {
+                (Some(NbtElement::Compound(compound)), NbtElement::Compound(other)) => {
+                    compound.copy_from(other)
+                }
+                _ => {
+                    self.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    fn keys_matching(&self, prefix: &str) -> Vec<&str> {
+        self.keys()
+            .filter(|e| e.starts_with(prefix))
+            .map(String::as_str)
+            .collect()
+    }
 }
 
-/// [`fastnbt_rc::input::Input`] implementation for [`bytes::Buf`].
-pub struct BufInput<'a, T: bytes::Buf>(pub &'a mut T);
+/// Widen a numeric element into `Ok` for integers or `Err` for floats.
+///
+/// Returns `None` if the element is not numeric.
+fn number_of(element: &NbtElement) -> Option<Result<i64, f64>> {
+    match element {
+        NbtElement::Byte(value) => Some(Ok(*value as i64)),
+        NbtElement::Short(value) => Some(Ok(*value as i64)),
+        NbtElement::Int(value) => Some(Ok(*value as i64)),
+        NbtElement::Long(value) => Some(Ok(*value)),
+        NbtElement::Float(value) => Some(Err(*value as f64)),
+        NbtElement::Double(value) => Some(Err(*value)),
+        _ => None,
+    }
+}
 
-impl<'de, T: bytes::Buf> fastnbt_rc::input::Input<'de> for BufInput<'de, T> {
-    fn consume_byte(&mut self) -> fastnbt_rc::error::Result<u8> {
-        Ok(self.0.get_u8())
+/// A [`NbtCompound`] wrapper which implements [`std::hash::Hash`]
+/// through [`NbtCompoundExt::hash_stable`], so it can be used
+/// as a key of maps and sets.
+///
+/// Floating point values are compared by their bit patterns, in
+/// line with the hash, so a compound containing `NaN` equals itself.
+#[derive(Clone, Default)]
+pub struct HashableNbtCompound(pub NbtCompound);
+
+impl PartialEq for HashableNbtCompound {
+    fn eq(&self, other: &Self) -> bool {
+        compound_bits_eq(&self.0, &other.0)
     }
+}
 
-    fn ignore_str(&mut self) -> fastnbt_rc::error::Result<()> {
-        let len = self.0.get_u16() as usize;
-        self.ignore_bytes(len)
+impl std::hash::Hash for HashableNbtCompound {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash_stable(state)
     }
+}
 
-    fn ignore_bytes(&mut self, size: usize) -> fastnbt_rc::error::Result<()> {
-        for _ in 0..size {
-            self.0.get_u8();
+impl Eq for HashableNbtCompound {}
+
+fn compound_bits_eq(a: &NbtCompound, b: &NbtCompound) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(key, value)| b.get(key).is_some_and(|e| element_bits_eq(value, e)))
+}
+
+fn element_bits_eq(a: &NbtElement, b: &NbtElement) -> bool {
+    match (a, b) {
+        (NbtElement::Float(a), NbtElement::Float(b)) => a.to_bits() == b.to_bits(),
+        (NbtElement::Double(a), NbtElement::Double(b)) => a.to_bits() == b.to_bits(),
+        (NbtElement::List(a), NbtElement::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|e| element_bits_eq(e.0, e.1))
         }
-        Ok(())
+        (NbtElement::Compound(a), NbtElement::Compound(b)) => compound_bits_eq(a, b),
+        _ => a == b,
     }
+}
 
-    fn consume_str<'s>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-    ) -> fastnbt_rc::error::Result<fastnbt_rc::input::Reference<'de, 's, str>> {
-        let n = self.0.get_u16() as usize;
-        scratch.clear();
-        for i in 0..n {
-            scratch[i] = self.0.get_u8();
+/// Some extensions for [`NbtType`].
+pub trait NbtTypeExt: Sized {
+    /// The type byte of this type, as used in the binary format.
+    fn id(&self) -> u8;
+    /// Get a type from its type byte, or `None` if the byte is unknown.
+    fn from_id(id: u8) -> Option<Self>;
+}
+
+impl NbtTypeExt for NbtType {
+    fn id(&self) -> u8 {
+        match self {
+            NbtType::End => 0,
+            NbtType::Byte => 1,
+            NbtType::Short => 2,
+            NbtType::Int => 3,
+            NbtType::Long => 4,
+            NbtType::Float => 5,
+            NbtType::Double => 6,
+            NbtType::ByteArray => 7,
+            NbtType::String => 8,
+            NbtType::List => 9,
+            NbtType::Compound => 10,
+            NbtType::IntArray => 11,
+            NbtType::LongArray => 12,
         }
+    }
 
-        let str = cesu8::from_java_cesu8(scratch).map_err(|_| {
-            fastnbt_rc::error::Error::custom(format!("Non-unicode string: {:?}", scratch))
-        })?;
+    fn from_id(id: u8) -> Option<Self> {
+        Some(match id {
+            0 => NbtType::End,
+            1 => NbtType::Byte,
+            2 => NbtType::Short,
+            3 => NbtType::Int,
+            4 => NbtType::Long,
+            5 => NbtType::Float,
+            6 => NbtType::Double,
+            7 => NbtType::ByteArray,
+            8 => NbtType::String,
+            9 => NbtType::List,
+            10 => NbtType::Compound,
+            11 => NbtType::IntArray,
+            12 => NbtType::LongArray,
+            _ => return None,
+        })
+    }
+}
 
-        Ok(match str {
-            std::borrow::Cow::Borrowed(_) => fastnbt_rc::input::Reference::Copied(unsafe {
-                std::str::from_utf8_unchecked(scratch)
-            }),
-            std::borrow::Cow::Owned(s) => {
-                *scratch = s.into_bytes();
-                fastnbt_rc::input::Reference::Copied(unsafe {
-                    std::str::from_utf8_unchecked(scratch)
-                })
+/// Some extensions for [`NbtElement`].
+pub trait NbtElementExt {
+    /// The type byte of this element.
+    fn get_type(&self) -> u8 {
+        self.get_nbt_type().id()
+    }
+
+    /// The [`NbtType`] of this element.
+    fn get_nbt_type(&self) -> NbtType;
+
+    /// Feed this element into the given hasher.
+    ///
+    /// Floats are hashed by their bit patterns and compounds are
+    /// hashed independent from the iteration order of entries.
+    fn hash_stable<H: std::hash::Hasher>(&self, state: &mut H);
+
+    /// Whether this element equals the other element, treating
+    /// floats within `epsilon` as equal and `NaN`s as equal to each other.
+    ///
+    /// Lists and compounds are compared recursively.
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool;
+
+    /// Estimated heap memory owned by this element in bytes,
+    /// excluding the size of the element itself.
+    ///
+    /// This accounts for actual Rust allocations (capacities of strings,
+    /// vectors and maps) rather than the vanilla nbt size accounting.
+    fn heap_size(&self) -> usize;
+
+    /// Size of this element in bytes as vanilla accounts it,
+    /// used for budgeting nbt from untrusted sources.
+    fn size_in_bytes(&self) -> usize;
+}
+
+impl NbtElementExt for NbtElement {
+    fn get_nbt_type(&self) -> NbtType {
+        match self {
+            NbtElement::Byte(_) => NbtType::Byte,
+            NbtElement::Short(_) => NbtType::Short,
+            NbtElement::Int(_) => NbtType::Int,
+            NbtElement::Long(_) => NbtType::Long,
+            NbtElement::Float(_) => NbtType::Float,
+            NbtElement::Double(_) => NbtType::Double,
+            NbtElement::String(_) => NbtType::String,
+            NbtElement::ByteArray(_) => NbtType::ByteArray,
+            NbtElement::IntArray(_) => NbtType::IntArray,
+            NbtElement::LongArray(_) => NbtType::LongArray,
+            NbtElement::List(_) => NbtType::List,
+            NbtElement::Compound(_) => NbtType::Compound,
+        }
+    }
+
+    fn hash_stable<H: std::hash::Hasher>(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        state.write_u8(self.get_type());
+        match self {
+            NbtElement::Byte(value) => value.hash(state),
+            NbtElement::Short(value) => value.hash(state),
+            NbtElement::Int(value) => value.hash(state),
+            NbtElement::Long(value) => value.hash(state),
+            NbtElement::Float(value) => value.to_bits().hash(state),
+            NbtElement::Double(value) => value.to_bits().hash(state),
+            NbtElement::String(value) => value.hash(state),
+            NbtElement::ByteArray(value) => value.iter().as_slice().hash(state),
+            NbtElement::IntArray(value) => value.iter().as_slice().hash(state),
+            NbtElement::LongArray(value) => value.iter().as_slice().hash(state),
+            NbtElement::List(value) => {
+                state.write_usize(value.len());
+                value.iter().for_each(|e| e.hash_stable(state));
             }
-        })
+            NbtElement::Compound(value) => value.hash_stable(state),
+        }
     }
 
-    fn consume_bytes<'s>(
-        &'s mut self,
-        n: usize,
-        scratch: &'s mut Vec<u8>,
-    ) -> fastnbt_rc::error::Result<fastnbt_rc::input::Reference<'de, 's, [u8]>> {
-        scratch.clear();
-        for i in 0..n {
-            scratch[i] = self.0.get_u8();
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        fn f_eq(a: f64, b: f64, epsilon: f64) -> bool {
+            (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= epsilon
+        }
+
+        match (self, other) {
+            (NbtElement::Float(a), NbtElement::Float(b)) => f_eq(*a as f64, *b as f64, epsilon),
+            (NbtElement::Double(a), NbtElement::Double(b)) => f_eq(*a, *b, epsilon),
+            (NbtElement::List(a), NbtElement::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|e| e.0.approx_eq(e.1, epsilon))
+            }
+            (NbtElement::Compound(a), NbtElement::Compound(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, value)| b.get(key).is_some_and(|e| value.approx_eq(e, epsilon)))
+            }
+            _ => self == other,
         }
-        Ok(fastnbt_rc::input::Reference::Copied(scratch.as_slice()))
     }
 
-    fn consume_i16(&mut self) -> fastnbt_rc::error::Result<i16> {
-        Ok(self.0.get_i16())
+    fn size_in_bytes(&self) -> usize {
+        match self {
+            NbtElement::Byte(_) => 9,
+            NbtElement::Short(_) => 10,
+            NbtElement::Int(_) | NbtElement::Float(_) => 12,
+            NbtElement::Long(_) | NbtElement::Double(_) => 16,
+            NbtElement::String(value) => 36 + 2 * value.encode_utf16().count(),
+            NbtElement::ByteArray(value) => 24 + value.iter().len(),
+            NbtElement::IntArray(value) => 24 + 4 * value.iter().len(),
+            NbtElement::LongArray(value) => 24 + 8 * value.iter().len(),
+            NbtElement::List(value) => {
+                37 + 4 * value.len() + value.iter().map(Self::size_in_bytes).sum::<usize>()
+            }
+            NbtElement::Compound(value) => {
+                48 + value
+                    .iter()
+                    .map(|(key, value)| {
+                        28 + 2 * key.encode_utf16().count() + 36 + value.size_in_bytes()
+                    })
+                    .sum::<usize>()
+            }
+        }
     }
 
-    fn consume_i32(&mut self) -> fastnbt_rc::error::Result<i32> {
-        Ok(self.0.get_i32())
+    fn heap_size(&self) -> usize {
+        use std::mem::size_of;
+
+        match self {
+            NbtElement::String(value) => value.capacity(),
+            NbtElement::ByteArray(value) => value.iter().len() * size_of::<i8>(),
+            NbtElement::IntArray(value) => value.iter().len() * size_of::<i32>(),
+            NbtElement::LongArray(value) => value.iter().len() * size_of::<i64>(),
+            NbtElement::List(value) => {
+                value.capacity() * size_of::<NbtElement>()
+                    + value.iter().map(NbtElement::heap_size).sum::<usize>()
+            }
+            NbtElement::Compound(value) => {
+                // one control byte per bucket in hashbrown-based maps
+                value.capacity() * (size_of::<(String, NbtElement)>() + 1)
+                    + value
+                        .iter()
+                        .map(|(key, value)| key.capacity() + value.heap_size())
+                        .sum::<usize>()
+            }
+            _ => 0,
+        }
     }
+}
 
-    fn consume_i64(&mut self) -> fastnbt_rc::error::Result<i64> {
-        Ok(self.0.get_i64())
+/// Maximum nesting depth of lists and compounds when reading, the same as vanilla.
+pub const MAX_DEPTH: usize = 512;
+
+/// Maximum number of values allocated up front when reading arrays
+/// and lists, since the claimed length can't be trusted.
+const PREALLOCATION_CAP: usize = 1024;
+
+/// A reader of binary nbt, checking type ids, strings and the nesting depth.
+struct NbtReader<R> {
+    input: R,
+    depth: usize,
+    /// Entry cap of each compound read.
+    entry_limit: Option<usize>,
+    /// Cap of the accounted size, see [`NbtElementExt::size_in_bytes`].
+    quota: Option<usize>,
+    used: usize,
+}
+
+impl<R: std::io::Read> NbtReader<R> {
+    fn new(input: R) -> Self {
+        Self {
+            input,
+            depth: 0,
+            entry_limit: None,
+            quota: None,
+            used: 0,
+        }
     }
 
-    fn consume_f32(&mut self) -> fastnbt_rc::error::Result<f32> {
-        Ok(self.0.get_f32())
+    /// Account read bytes the same as vanilla, failing if over the quota.
+    ///
+    /// This is called before reading arrays and lists, so their
+    /// lengths are checked before any allocation.
+    fn account(&mut self, bytes: usize) -> Result<(), NbtError> {
+        self.used = self.used.saturating_add(bytes);
+        match self.quota {
+            Some(quota) if self.used > quota => Err(NbtError::SizeLimit),
+            _ => Ok(()),
+        }
     }
 
-    fn consume_f64(&mut self) -> fastnbt_rc::error::Result<f64> {
-        Ok(self.0.get_f64())
+    fn read_array_len(&mut self, element_size: usize) -> Result<usize, NbtError> {
+        let len = self.read_len()?;
+        self.account(24_usize.saturating_add(len.saturating_mul(element_size)))?;
+        Ok(len)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        let mut bytes = [0_u8; N];
+        self.input.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_type(&mut self) -> Result<NbtType, NbtError> {
+        let id = self.read_array::<1>()?[0];
+        NbtType::from_id(id).ok_or(NbtError::InvalidType(id))
+    }
+
+    /// Read the length of an array or list, failing if negative.
+    fn read_len(&mut self) -> Result<usize, NbtError> {
+        usize::try_from(i32::from_be_bytes(self.read_array()?)).map_err(|_| NbtError::SizeLimit)
+    }
+
+    fn read_str(&mut self) -> Result<String, NbtError> {
+        let len = u16::from_be_bytes(self.read_array()?) as usize;
+        let mut bytes = vec![0_u8; len];
+        self.input.read_exact(&mut bytes)?;
+        match cesu8::from_java_cesu8(&bytes) {
+            Ok(value) => Ok(value.into_owned()),
+            Err(_) => Err(NbtError::Utf8(bytes)),
+        }
+    }
+
+    /// Read `len` values, growing the values as read
+    /// instead of trusting `len` for allocation.
+    fn read_values<T, F>(&mut self, len: usize, mut f: F) -> Result<Vec<T>, NbtError>
+    where
+        F: FnMut(&mut Self) -> Result<T, NbtError>,
+    {
+        let mut values = Vec::with_capacity(len.min(PREALLOCATION_CAP));
+        for _ in 0..len {
+            values.push(f(self)?);
+        }
+        Ok(values)
+    }
+
+    fn push_depth(&mut self) -> Result<(), NbtError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(NbtError::DepthLimit);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Read the payload of an element of the given type.
+    ///
+    /// Elements without nested elements are read by [`Self::read_flat_payload`],
+    /// keeping the stack frames of this recursion small.
+    fn read_payload(&mut self, ty: NbtType) -> Result<NbtElement, NbtError> {
+        match ty {
+            NbtType::List => self.read_list().map(NbtElement::List),
+            NbtType::Compound => self.read_compound().map(NbtElement::Compound),
+            _ => self.read_flat_payload(ty),
+        }
+    }
+
+    fn read_flat_payload(&mut self, ty: NbtType) -> Result<NbtElement, NbtError> {
+        match ty {
+            NbtType::Byte => self.account(9)?,
+            NbtType::Short => self.account(10)?,
+            NbtType::Int | NbtType::Float => self.account(12)?,
+            NbtType::Long | NbtType::Double => self.account(16)?,
+            _ => (),
+        }
+
+        Ok(match ty {
+            NbtType::Byte => NbtElement::Byte(i8::from_be_bytes(self.read_array()?)),
+            NbtType::Short => NbtElement::Short(i16::from_be_bytes(self.read_array()?)),
+            NbtType::Int => NbtElement::Int(i32::from_be_bytes(self.read_array()?)),
+            NbtType::Long => NbtElement::Long(i64::from_be_bytes(self.read_array()?)),
+            NbtType::Float => NbtElement::Float(f32::from_be_bytes(self.read_array()?)),
+            NbtType::Double => NbtElement::Double(f64::from_be_bytes(self.read_array()?)),
+            NbtType::String => {
+                let value = self.read_str()?;
+                self.account(36 + 2 * value.encode_utf16().count())?;
+                NbtElement::String(value)
+            }
+            NbtType::ByteArray => {
+                let len = self.read_array_len(1)?;
+                NbtElement::ByteArray(ByteArray::new(
+                    self.read_values(len, |e| Ok(i8::from_be_bytes(e.read_array()?)))?,
+                ))
+            }
+            NbtType::IntArray => {
+                let len = self.read_array_len(4)?;
+                NbtElement::IntArray(IntArray::new(
+                    self.read_values(len, |e| Ok(i32::from_be_bytes(e.read_array()?)))?,
+                ))
+            }
+            NbtType::LongArray => {
+                let len = self.read_array_len(8)?;
+                NbtElement::LongArray(LongArray::new(
+                    self.read_values(len, |e| Ok(i64::from_be_bytes(e.read_array()?)))?,
+                ))
+            }
+            NbtType::End | NbtType::List | NbtType::Compound => {
+                return Err(NbtError::InvalidType(ty.id()))
+            }
+        })
+    }
+
+    fn read_list(&mut self) -> Result<NbtList, NbtError> {
+        self.push_depth()?;
+        self.account(37)?;
+        let ty = self.read_type()?;
+        let len = self.read_len()?;
+        self.account(len.saturating_mul(4))?;
+        if ty == NbtType::End && len > 0 {
+            return Err(NbtError::InvalidType(0));
+        }
+        let list = self.read_values(len, |e| e.read_payload(ty))?;
+        self.depth -= 1;
+        Ok(list)
+    }
+
+    fn read_compound(&mut self) -> Result<NbtCompound, NbtError> {
+        self.push_depth()?;
+        self.account(48)?;
+        let mut compound = self.entry_limit.map_or_else(
+            LimitedNbtCompound::new,
+            LimitedNbtCompound::with_capacity_limit,
+        );
+        while let Some((ty, key)) = self.read_entry_header()? {
+            let value = self.read_payload(ty)?;
+            self.put_entry(&mut compound, &key, value)?;
+        }
+        self.depth -= 1;
+        Ok(compound.into_inner())
+    }
+
+    /// Read the type and key of the next compound entry,
+    /// or `None` at the end of the compound.
+    fn read_entry_header(&mut self) -> Result<Option<(NbtType, String)>, NbtError> {
+        let ty = self.read_type()?;
+        if ty == NbtType::End {
+            return Ok(None);
+        }
+        let key = self.read_str()?;
+        self.account(28 + 2 * key.encode_utf16().count())?;
+        Ok(Some((ty, key)))
+    }
+
+    fn put_entry(
+        &mut self,
+        compound: &mut LimitedNbtCompound,
+        key: &str,
+        value: NbtElement,
+    ) -> Result<(), NbtError> {
+        if compound.put(key, value)?.is_none() {
+            self.account(36)?;
+        }
+        Ok(())
+    }
+
+    /// Read a named root compound.
+    fn read_named(&mut self) -> Result<(String, NbtCompound), NbtError> {
+        let id = self.read_array::<1>()?[0];
+        if id != NbtType::Compound.id() {
+            return Err(NbtError::InvalidType(id));
+        }
+        let name = self.read_str()?;
+        Ok((name, self.read_compound()?))
+    }
+}
+
+/// A writer of binary nbt, checking that lists are homogeneous.
+struct NbtWriter<W> {
+    out: W,
+}
+
+impl<W: std::io::Write> NbtWriter<W> {
+    fn write_str(&mut self, value: &str) -> Result<(), NbtError> {
+        let bytes = cesu8::to_java_cesu8(value);
+        let len = u16::try_from(bytes.len()).map_err(|_| NbtError::SizeLimit)?;
+        self.out.write_all(&len.to_be_bytes())?;
+        self.out.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<(), NbtError> {
+        let len = i32::try_from(len).map_err(|_| NbtError::SizeLimit)?;
+        self.out.write_all(&len.to_be_bytes())?;
+        Ok(())
+    }
+
+    fn write_payload(&mut self, element: &NbtElement) -> Result<(), NbtError> {
+        match element {
+            NbtElement::Byte(value) => self.out.write_all(&value.to_be_bytes())?,
+            NbtElement::Short(value) => self.out.write_all(&value.to_be_bytes())?,
+            NbtElement::Int(value) => self.out.write_all(&value.to_be_bytes())?,
+            NbtElement::Long(value) => self.out.write_all(&value.to_be_bytes())?,
+            NbtElement::Float(value) => self.out.write_all(&value.to_be_bytes())?,
+            NbtElement::Double(value) => self.out.write_all(&value.to_be_bytes())?,
+            NbtElement::String(value) => self.write_str(value)?,
+            NbtElement::ByteArray(value) => {
+                self.write_len(value.iter().len())?;
+                for e in value.iter() {
+                    self.out.write_all(&e.to_be_bytes())?;
+                }
+            }
+            NbtElement::IntArray(value) => {
+                self.write_len(value.iter().len())?;
+                for e in value.iter() {
+                    self.out.write_all(&e.to_be_bytes())?;
+                }
+            }
+            NbtElement::LongArray(value) => {
+                self.write_len(value.iter().len())?;
+                for e in value.iter() {
+                    self.out.write_all(&e.to_be_bytes())?;
+                }
+            }
+            NbtElement::List(value) => {
+                let ty = value.first().map_or(NbtType::End, |e| e.get_nbt_type());
+                if value.iter().any(|e| e.get_nbt_type() != ty) {
+                    return Err(NbtError::HeterogeneousList);
+                }
+                self.out.write_all(&[ty.id()])?;
+                self.write_len(value.len())?;
+                for e in value {
+                    self.write_payload(e)?;
+                }
+            }
+            NbtElement::Compound(value) => self.write_compound(value)?,
+        }
+        Ok(())
+    }
+
+    fn write_compound(&mut self, compound: &NbtCompound) -> Result<(), NbtError> {
+        for (key, value) in compound {
+            self.out.write_all(&[value.get_nbt_type().id()])?;
+            self.write_str(key)?;
+            self.write_payload(value)?;
+        }
+        self.out.write_all(&[NbtType::End.id()])?;
+        Ok(())
+    }
+}
+
+/// Read a named root compound, returning its name and the compound.
+///
+/// Lists and compounds nested deeper than [`MAX_DEPTH`] are rejected,
+/// and failures carry a [`NbtError`] as the inner error.
+pub fn read_named(input: impl std::io::Read) -> std::io::Result<(String, NbtCompound)> {
+    NbtReader::new(input)
+        .read_named()
+        .map_err(NbtError::into_io_error)
+}
+
+/// Read a named root compound like [`read_named`], failing with
+/// [`NbtError::SizeLimit`] if any compound has more than `entry_limit` entries.
+///
+/// This guards memory when reading from untrusted sources.
+pub fn read_named_with_limit(
+    input: impl std::io::Read,
+    entry_limit: usize,
+) -> std::io::Result<(String, NbtCompound)> {
+    let mut reader = NbtReader::new(input);
+    reader.entry_limit = Some(entry_limit);
+    reader.read_named().map_err(NbtError::into_io_error)
+}
+
+/// Read a named root compound like [`read_named`], failing with
+/// [`NbtError::SizeLimit`] once the read elements are accounted more than
+/// `max_bytes`, the same as the vanilla size tracker.
+/// A `max_bytes` of `0` means unlimited.
+///
+/// See [`NbtElementExt::size_in_bytes`] for the accounting.
+pub fn read_named_with_quota(
+    input: impl std::io::Read,
+    max_bytes: usize,
+) -> std::io::Result<(String, NbtCompound)> {
+    let mut reader = NbtReader::new(input);
+    reader.quota = (max_bytes != 0).then_some(max_bytes);
+    reader.read_named().map_err(NbtError::into_io_error)
+}
+
+/// Write a root compound with the given name in the vanilla binary format,
+/// which is read back by [`read_named`].
+///
+/// Lists with elements of different types fail with [`NbtError::HeterogeneousList`].
+pub fn write_named(
+    name: &str,
+    compound: &NbtCompound,
+    out: impl std::io::Write,
+) -> std::io::Result<()> {
+    let mut writer = NbtWriter { out };
+    writer
+        .out
+        .write_all(&[NbtType::Compound.id()])
+        .map_err(NbtError::from)
+        .and_then(|_| writer.write_str(name))
+        .and_then(|_| writer.write_compound(compound))
+        .map_err(NbtError::into_io_error)
+}
+
+/// Read a gzip compressed named root compound, like `level.dat`.
+///
+/// See [`read_named`].
+pub fn read_compressed(input: impl std::io::Read) -> std::io::Result<(String, NbtCompound)> {
+    read_named(flate2::read::GzDecoder::new(input))
+}
+
+/// Write a gzip compressed named root compound.
+///
+/// See [`write_named`].
+pub fn write_compressed(
+    name: &str,
+    compound: &NbtCompound,
+    out: impl std::io::Write,
+) -> std::io::Result<()> {
+    let mut encoder = flate2::write::GzEncoder::new(out, flate2::Compression::default());
+    write_named(name, compound, &mut encoder)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Format a float in snbt with the `f` suffix.
+///
+/// The shortest representation which parses back to the same bits is used,
+/// as provided by the standard library float formatting.
+pub fn format_f32(value: f32) -> String {
+    format!("{value:?}f")
+}
+
+/// Format a double in snbt with the `d` suffix.
+///
+/// The shortest representation which parses back to the same bits is used,
+/// as provided by the standard library float formatting.
+pub fn format_f64(value: f64) -> String {
+    format!("{value:?}d")
+}
+
+/// Display wrapper formatting an element in snbt, the stringified nbt
+/// format used by vanilla commands.
+///
+/// Keys of compounds are sorted so the output is deterministic,
+/// and keys not matching `[A-Za-z0-9_.+-]+` are quoted.
+pub struct Snbt<'a>(pub &'a NbtElement);
+
+impl Snbt<'_> {
+    fn write_str(value: &str, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let quote = if value.contains('"') && !value.contains('\'') {
+            '\''
+        } else {
+            '"'
+        };
+
+        write!(f, "{quote}")?;
+        for c in value.chars() {
+            if c == quote || c == '\\' {
+                write!(f, "\\")?;
+            }
+            write!(f, "{c}")?;
+        }
+        write!(f, "{quote}")
+    }
+
+    fn write_array<T: std::fmt::Display>(
+        prefix: char,
+        suffix: &str,
+        values: &[T],
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "[{prefix};")?;
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{value}{suffix}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl std::fmt::Display for Snbt<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            NbtElement::Byte(value) => write!(f, "{value}b"),
+            NbtElement::Short(value) => write!(f, "{value}s"),
+            NbtElement::Int(value) => write!(f, "{value}"),
+            NbtElement::Long(value) => write!(f, "{value}L"),
+            NbtElement::Float(value) => f.write_str(&format_f32(*value)),
+            NbtElement::Double(value) => f.write_str(&format_f64(*value)),
+            NbtElement::String(value) => Self::write_str(value, f),
+            NbtElement::ByteArray(value) => Self::write_array('B', "b", value.iter().as_slice(), f),
+            NbtElement::IntArray(value) => Self::write_array('I', "", value.iter().as_slice(), f),
+            NbtElement::LongArray(value) => Self::write_array('L', "L", value.iter().as_slice(), f),
+            NbtElement::List(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    Snbt(value).fmt(f)?;
+                }
+                f.write_str("]")
+            }
+            NbtElement::Compound(values) => {
+                let mut entries = values.iter().collect::<Vec<_>>();
+                entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+
+                    if !key.is_empty()
+                        && key.chars().all(|c| {
+                            c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-')
+                        })
+                    {
+                        f.write_str(key)?;
+                    } else {
+                        Self::write_str(key, f)?;
+                    }
+                    f.write_str(":")?;
+                    Snbt(value).fmt(f)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// [`fastnbt_rc::input::Input`] implementation for [`bytes::Buf`].
+pub struct BufInput<'a, T: bytes::Buf>(pub &'a mut T);
+
+impl<T: bytes::Buf> BufInput<'_, T> {
+    /// Fails with [`NbtError::UnexpectedEnd`] if less than `len` bytes remain.
+    fn ensure(&self, len: usize) -> fastnbt_rc::error::Result<()> {
+        if self.0.remaining() < len {
+            Err(fastnbt_rc::error::Error::custom(NbtError::UnexpectedEnd))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'de, T: bytes::Buf> fastnbt_rc::input::Input<'de> for BufInput<'de, T> {
+    fn consume_byte(&mut self) -> fastnbt_rc::error::Result<u8> {
+        self.ensure(1)?;
+        Ok(self.0.get_u8())
+    }
+
+    fn ignore_str(&mut self) -> fastnbt_rc::error::Result<()> {
+        self.ensure(2)?;
+        let len = self.0.get_u16() as usize;
+        self.ignore_bytes(len)
+    }
+
+    fn ignore_bytes(&mut self, size: usize) -> fastnbt_rc::error::Result<()> {
+        self.ensure(size)?;
+        self.0.advance(size);
+        Ok(())
+    }
+
+    fn consume_str<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> fastnbt_rc::error::Result<fastnbt_rc::input::Reference<'de, 's, str>> {
+        self.ensure(2)?;
+        let n = self.0.get_u16() as usize;
+        self.ensure(n)?;
+        scratch.clear();
+        for _ in 0..n {
+            scratch.push(self.0.get_u8());
+        }
+
+        let str = cesu8::from_java_cesu8(scratch)
+            .map_err(|_| fastnbt_rc::error::Error::custom(NbtError::Utf8(scratch.clone())))?;
+
+        Ok(match str {
+            std::borrow::Cow::Borrowed(_) => fastnbt_rc::input::Reference::Copied(unsafe {
+                std::str::from_utf8_unchecked(scratch)
+            }),
+            std::borrow::Cow::Owned(s) => {
+                *scratch = s.into_bytes();
+                fastnbt_rc::input::Reference::Copied(unsafe {
+                    std::str::from_utf8_unchecked(scratch)
+                })
+            }
+        })
+    }
+
+    fn consume_bytes<'s>(
+        &'s mut self,
+        n: usize,
+        scratch: &'s mut Vec<u8>,
+    ) -> fastnbt_rc::error::Result<fastnbt_rc::input::Reference<'de, 's, [u8]>> {
+        self.ensure(n)?;
+        scratch.clear();
+        for _ in 0..n {
+            scratch.push(self.0.get_u8());
+        }
+        Ok(fastnbt_rc::input::Reference::Copied(scratch.as_slice()))
+    }
+
+    fn consume_i16(&mut self) -> fastnbt_rc::error::Result<i16> {
+        self.ensure(2)?;
+        Ok(self.0.get_i16())
+    }
+
+    fn consume_i32(&mut self) -> fastnbt_rc::error::Result<i32> {
+        self.ensure(4)?;
+        Ok(self.0.get_i32())
+    }
+
+    fn consume_i64(&mut self) -> fastnbt_rc::error::Result<i64> {
+        self.ensure(8)?;
+        Ok(self.0.get_i64())
+    }
+
+    fn consume_f32(&mut self) -> fastnbt_rc::error::Result<f32> {
+        self.ensure(4)?;
+        Ok(self.0.get_f32())
+    }
+
+    fn consume_f64(&mut self) -> fastnbt_rc::error::Result<f64> {
+        self.ensure(8)?;
+        Ok(self.0.get_f64())
+    }
+}
+
+/// Parsing of snbt, the stringified nbt format written by [`Snbt`].
+pub mod snbt {
+    use super::{NbtCompound, NbtElement, NbtElementExt};
+
+    /// Parse an element from snbt, tolerating whitespaces between tokens.
+    ///
+    /// Errors report the byte offset where parsing failed.
+    pub fn parse(input: &str) -> anyhow::Result<NbtElement> {
+        let mut reader = Reader { input, pos: 0 };
+        let value = reader.read_value()?;
+        reader.skip_whitespace();
+
+        if reader.pos < input.len() {
+            Err(reader.error("Trailing data"))
+        } else {
+            Ok(value)
+        }
+    }
+
+    struct Reader<'a> {
+        input: &'a str,
+        pos: usize,
+    }
+
+    impl<'a> Reader<'a> {
+        fn error(&self, message: &str) -> anyhow::Error {
+            anyhow::anyhow!("{message} at offset {}", self.pos)
+        }
+
+        fn peek(&self) -> Option<char> {
+            self.input[self.pos..].chars().next()
+        }
+
+        fn skip_whitespace(&mut self) {
+            while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+                self.pos += c.len_utf8();
+            }
+        }
+
+        fn expect(&mut self, expected: char) -> anyhow::Result<()> {
+            self.skip_whitespace();
+            if self.peek() == Some(expected) {
+                self.pos += 1;
+                Ok(())
+            } else {
+                Err(self.error(&format!("Expected '{expected}'")))
+            }
+        }
+
+        fn read_value(&mut self) -> anyhow::Result<NbtElement> {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => self.read_compound(),
+                Some('[') => self.read_list_or_array(),
+                Some('"' | '\'') => Ok(NbtElement::String(self.read_quoted()?)),
+                Some(_) => {
+                    let value = self.read_unquoted();
+                    if value.is_empty() {
+                        Err(self.error("Expected value"))
+                    } else {
+                        parse_unquoted(value).ok_or_else(|| self.error("Integer out of range"))
+                    }
+                }
+                None => Err(self.error("Expected value")),
+            }
+        }
+
+        fn read_unquoted(&mut self) -> &'a str {
+            let input = self.input;
+            let start = self.pos;
+            while let Some(c) = self
+                .peek()
+                .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-'))
+            {
+                self.pos += c.len_utf8();
+            }
+            &input[start..self.pos]
+        }
+
+        fn read_quoted(&mut self) -> anyhow::Result<String> {
+            let quote = self.peek().ok_or_else(|| self.error("Expected string"))?;
+            self.pos += 1;
+            let mut value = String::new();
+
+            loop {
+                let c = self.peek().ok_or_else(|| self.error("Unclosed string"))?;
+                self.pos += c.len_utf8();
+
+                if c == quote {
+                    return Ok(value);
+                } else if c == '\\' {
+                    match self.peek() {
+                        Some(e) if e == quote || e == '\\' => {
+                            self.pos += 1;
+                            value.push(e);
+                        }
+                        _ => return Err(self.error("Invalid escape")),
+                    }
+                } else {
+                    value.push(c);
+                }
+            }
+        }
+
+        fn read_key(&mut self) -> anyhow::Result<String> {
+            self.skip_whitespace();
+            let key = match self.peek() {
+                Some('"' | '\'') => self.read_quoted()?,
+                _ => self.read_unquoted().to_string(),
+            };
+
+            if key.is_empty() {
+                Err(self.error("Expected key"))
+            } else {
+                Ok(key)
+            }
+        }
+
+        /// Whether the next token is the closing `end`, skipping a separating comma otherwise.
+        fn next_or_end(&mut self, end: char) -> anyhow::Result<bool> {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(c) if c == end => {
+                    self.pos += 1;
+                    Ok(true)
+                }
+                Some(',') => {
+                    self.pos += 1;
+                    Ok(false)
+                }
+                _ => Err(self.error(&format!("Expected ',' or '{end}'"))),
+            }
+        }
+
+        fn read_compound(&mut self) -> anyhow::Result<NbtElement> {
+            self.expect('{')?;
+            let mut compound = NbtCompound::new();
+
+            self.skip_whitespace();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(NbtElement::Compound(compound));
+            }
+
+            loop {
+                let key = self.read_key()?;
+                self.expect(':')?;
+                compound.insert(key, self.read_value()?);
+
+                if self.next_or_end('}')? {
+                    return Ok(NbtElement::Compound(compound));
+                }
+            }
+        }
+
+        fn read_list_or_array(&mut self) -> anyhow::Result<NbtElement> {
+            self.expect('[')?;
+            self.skip_whitespace();
+
+            let rest = &self.input[self.pos..];
+            let mut chars = rest.chars();
+            if let (Some(ty @ ('B' | 'I' | 'L')), Some(';')) = (chars.next(), chars.next()) {
+                self.pos += 2;
+                return self.read_array(ty);
+            }
+
+            let mut values = Vec::new();
+            self.skip_whitespace();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(NbtElement::List(values));
+            }
+
+            loop {
+                let start = self.pos;
+                let value = self.read_value()?;
+                if values
+                    .first()
+                    .is_some_and(|e: &NbtElement| e.get_type() != value.get_type())
+                {
+                    return Err(anyhow::anyhow!(
+                        "Mismatched list element type at offset {start}"
+                    ));
+                }
+                values.push(value);
+
+                if self.next_or_end(']')? {
+                    return Ok(NbtElement::List(values));
+                }
+            }
+        }
+
+        fn read_array(&mut self, ty: char) -> anyhow::Result<NbtElement> {
+            let mut bytes = Vec::new();
+            let mut ints = Vec::new();
+            let mut longs = Vec::new();
+
+            self.skip_whitespace();
+            if self.peek() != Some(']') {
+                loop {
+                    self.skip_whitespace();
+                    let start = self.pos;
+                    let value = parse_unquoted(self.read_unquoted());
+                    match (ty, value) {
+                        (_, None) => return Err(self.error("Integer out of range")),
+                        ('B', Some(NbtElement::Byte(e))) => bytes.push(e),
+                        ('I', Some(NbtElement::Int(e))) => ints.push(e),
+                        ('L', Some(NbtElement::Long(e))) => longs.push(e),
+                        _ => {
+                            return Err(anyhow::anyhow!(
+                                "Invalid element in {ty} array at offset {start}"
+                            ))
+                        }
+                    }
+
+                    if self.next_or_end(']')? {
+                        break;
+                    }
+                }
+            } else {
+                self.pos += 1;
+            }
+
+            Ok(match ty {
+                'B' => NbtElement::ByteArray(super::ByteArray::new(bytes)),
+                'I' => NbtElement::IntArray(super::IntArray::new(ints)),
+                _ => NbtElement::LongArray(super::LongArray::new(longs)),
+            })
+        }
+    }
+
+    /// Parse an unquoted token into a typed number,
+    /// a boolean byte, or a plain string.
+    ///
+    /// Unsuffixed integers outside the int range are parsed as longs,
+    /// and `None` is returned if they are outside the long range too.
+    fn parse_unquoted(value: &str) -> Option<NbtElement> {
+        fn typed<T: std::str::FromStr>(value: &str, suffixes: [char; 2]) -> Option<T> {
+            value
+                .strip_suffix(suffixes)
+                .filter(|e| !e.is_empty())
+                .and_then(|e| e.parse().ok())
+        }
+
+        let is_number = value
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.'));
+
+        if is_number {
+            if let Some(e) = typed(value, ['b', 'B']) {
+                return Some(NbtElement::Byte(e));
+            }
+            if let Some(e) = typed(value, ['s', 'S']) {
+                return Some(NbtElement::Short(e));
+            }
+            if let Some(e) = typed(value, ['l', 'L']) {
+                return Some(NbtElement::Long(e));
+            }
+            if let Some(e) = typed(value, ['f', 'F']) {
+                return Some(NbtElement::Float(e));
+            }
+            if let Some(e) = typed(value, ['d', 'D']) {
+                return Some(NbtElement::Double(e));
+            }
+            if let Ok(e) = value.parse() {
+                return Some(NbtElement::Int(e));
+            }
+            let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+            if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+                return value.parse().ok().map(NbtElement::Long);
+            }
+            if value.contains('.') {
+                if let Ok(e) = value.parse() {
+                    return Some(NbtElement::Double(e));
+                }
+            }
+        }
+
+        Some(match value {
+            "true" => NbtElement::Byte(1),
+            "false" => NbtElement::Byte(0),
+            _ => NbtElement::String(value.to_string()),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::nbt::{ByteArray, IntArray, LongArray, NbtCompoundExt, Snbt};
+
+        #[test]
+        fn parse_values() {
+            assert_eq!(parse("1b").unwrap(), NbtElement::Byte(1));
+            assert_eq!(parse("-2s").unwrap(), NbtElement::Short(-2));
+            assert_eq!(parse("3").unwrap(), NbtElement::Int(3));
+            assert_eq!(parse("4L").unwrap(), NbtElement::Long(4));
+            assert_eq!(parse("0.5f").unwrap(), NbtElement::Float(0.5));
+            assert_eq!(parse("1.5").unwrap(), NbtElement::Double(1.5));
+            assert_eq!(parse("true").unwrap(), NbtElement::Byte(1));
+            assert_eq!(
+                parse("minecraft:stone").unwrap_err().to_string(),
+                "Trailing data at offset 9"
+            );
+            assert_eq!(
+                parse("'it''").unwrap_err().to_string(),
+                "Trailing data at offset 4"
+            );
+            assert_eq!(
+                parse("\"a\\\"b\"").unwrap(),
+                NbtElement::String("a\"b".to_string())
+            );
+        }
+
+        #[test]
+        fn unsuffixed_integers_out_of_int_range() {
+            assert_eq!(parse("2147483648").unwrap(), NbtElement::Long(1 << 31));
+            assert_eq!(
+                parse("-9223372036854775808").unwrap(),
+                NbtElement::Long(i64::MIN)
+            );
+            assert!(parse("9223372036854775808").is_err());
+            assert!(parse("[I; 2147483648]").is_err());
+        }
+
+        #[test]
+        fn typed_arrays() {
+            assert_eq!(
+                parse("[ I; 1, 2]").unwrap(),
+                NbtElement::IntArray(IntArray::new(vec![1, 2]))
+            );
+            assert_eq!(
+                parse("[B;1b, -1b]").unwrap(),
+                NbtElement::ByteArray(ByteArray::new(vec![1, -1]))
+            );
+            assert_eq!(
+                parse("[L;]").unwrap(),
+                NbtElement::LongArray(LongArray::new(Vec::new()))
+            );
+            assert!(parse("[I; 1b]").is_err());
+            assert!(parse("[1, 2b]").is_err());
+        }
+
+        #[test]
+        fn round_trip() {
+            let mut compound = NbtCompound::new();
+            compound.insert_str("name", "it's \"quoted\"");
+            compound.insert_i64_slice("longs", &[i64::MIN, 0]);
+            compound.insert_i32_list("ints", &[1, -1]);
+            compound.insert_f32("float", 0.1);
+            compound.insert_f64("double", -1e300);
+            compound.insert_i16("short", 7);
+            compound.insert_compound("empty", NbtCompound::new());
+            compound.insert_list("empty list", Vec::new());
+            let element = NbtElement::Compound(compound);
+
+            let snbt = Snbt(&element).to_string();
+            assert_eq!(parse(&snbt).unwrap(), element);
+        }
+    }
+}
+
+/// Queries of nested elements by vanilla-style paths.
+pub mod path {
+    use super::NbtElement;
+
+    /// A segment of a [`NbtPath`].
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub enum PathNode {
+        /// A key of compounds.
+        Key(String),
+        /// An index of lists, counting from the end if negative.
+        Index(i32),
+        /// All elements of lists.
+        All,
+    }
+
+    /// A path like `display.Lore[0]` or `Items[].id`, selecting nested elements.
+    ///
+    /// Keys can be bare or quoted, and compound filters are not supported.
+    /// Like vanilla, bare keys can't contain spaces, quotes, dots,
+    /// brackets or braces.
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct NbtPath {
+        nodes: Vec<PathNode>,
+    }
+
+    impl NbtPath {
+        pub fn parse(path: &str) -> anyhow::Result<Self> {
+            let mut nodes = Vec::new();
+            let mut chars = path.char_indices().peekable();
+            let mut expect_key = true;
+
+            while let Some(&(pos, c)) = chars.peek() {
+                match c {
+                    '[' => {
+                        chars.next();
+                        let mut index = String::new();
+                        loop {
+                            match chars.next() {
+                                Some((_, ']')) => break,
+                                Some((_, c)) => index.push(c),
+                                None => {
+                                    return Err(anyhow::anyhow!(
+                                        "Unclosed index at offset {pos} of path {path}"
+                                    ))
+                                }
+                            }
+                        }
+
+                        nodes.push(if index.is_empty() {
+                            PathNode::All
+                        } else {
+                            PathNode::Index(index.trim().parse().map_err(|_| {
+                                anyhow::anyhow!("Invalid index at offset {pos} of path {path}")
+                            })?)
+                        });
+                        expect_key = false;
+                    }
+                    '.' if !expect_key => {
+                        chars.next();
+                        expect_key = true;
+                    }
+                    '"' | '\'' if expect_key => {
+                        chars.next();
+                        let mut key = String::new();
+                        loop {
+                            match chars.next() {
+                                Some((_, e)) if e == c => break,
+                                Some((_, '\\')) => match chars.next() {
+                                    Some((_, e)) => key.push(e),
+                                    None => break,
+                                },
+                                Some((_, e)) => key.push(e),
+                                None => {
+                                    return Err(anyhow::anyhow!(
+                                        "Unclosed key at offset {pos} of path {path}"
+                                    ))
+                                }
+                            }
+                        }
+                        nodes.push(PathNode::Key(key));
+                        expect_key = false;
+                    }
+                    _ if expect_key => {
+                        let mut key = String::new();
+                        while let Some(&(_, e)) = chars.peek() {
+                            if matches!(e, '.' | '[' | ']' | '{' | '}' | ' ' | '"' | '\'') {
+                                break;
+                            }
+                            key.push(e);
+                            chars.next();
+                        }
+
+                        if key.is_empty() {
+                            return Err(anyhow::anyhow!(
+                                "Empty key at offset {pos} of path {path}"
+                            ));
+                        }
+                        nodes.push(PathNode::Key(key));
+                        expect_key = false;
+                    }
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "Unexpected '{c}' at offset {pos} of path {path}"
+                        ))
+                    }
+                }
+            }
+
+            if nodes.is_empty() || (expect_key && path.ends_with('.')) {
+                Err(anyhow::anyhow!("Incomplete path: {path}"))
+            } else {
+                Ok(Self { nodes })
+            }
+        }
+
+        pub fn nodes(&self) -> &[PathNode] {
+            &self.nodes
+        }
+
+        /// Get elements selected by this path from the root element,
+        /// or an empty vec if nothing matches.
+        pub fn get<'a>(&self, root: &'a NbtElement) -> Vec<&'a NbtElement> {
+            let mut elements = vec![root];
+
+            for node in self.nodes.iter() {
+                elements = elements
+                    .into_iter()
+                    .flat_map(|e| Self::select(node, e))
+                    .collect();
+
+                if elements.is_empty() {
+                    break;
+                }
+            }
+
+            elements
+        }
+
+        fn select<'a>(node: &PathNode, element: &'a NbtElement) -> Vec<&'a NbtElement> {
+            match (node, element) {
+                (PathNode::Key(key), NbtElement::Compound(compound)) => {
+                    compound.get(key).into_iter().collect()
+                }
+                (PathNode::Index(index), NbtElement::List(list)) => {
+                    let index = if *index < 0 {
+                        list.len() as i64 + *index as i64
+                    } else {
+                        *index as i64
+                    };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|e| list.get(e))
+                        .into_iter()
+                        .collect()
+                }
+                (PathNode::All, NbtElement::List(list)) => list.iter().collect(),
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    impl std::str::FromStr for NbtPath {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Self::parse(s)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::nbt::{NbtCompound, NbtCompoundExt};
+
+        fn item_data() -> NbtElement {
+            let mut display = NbtCompound::new();
+            display.insert_str_list("Lore", &["first", "second"]);
+
+            let items = ["minecraft:stone", "minecraft:apple"]
+                .into_iter()
+                .map(|id| {
+                    let mut item = NbtCompound::new();
+                    item.insert_str("id", id);
+                    NbtElement::Compound(item)
+                })
+                .collect();
+
+            let mut nbt = NbtCompound::new();
+            nbt.insert("display".to_string(), NbtElement::Compound(display));
+            nbt.insert_list("Items", items);
+            nbt.insert_i32("odd key", 1);
+            NbtElement::Compound(nbt)
+        }
+
+        fn strings(path: &str, root: &NbtElement) -> Vec<String> {
+            NbtPath::parse(path)
+                .unwrap()
+                .get(root)
+                .into_iter()
+                .map(|e| match e {
+                    NbtElement::String(value) => value.clone(),
+                    _ => panic!("{path} should select strings"),
+                })
+                .collect()
+        }
+
+        #[test]
+        fn select_paths() {
+            let root = item_data();
+            assert_eq!(strings("display.Lore[0]", &root), ["first"]);
+            assert_eq!(strings("display.Lore[-1]", &root), ["second"]);
+            assert_eq!(
+                strings("Items[].id", &root),
+                ["minecraft:stone", "minecraft:apple"]
+            );
+            assert_eq!(strings("'display'.\"Lore\"[1]", &root), ["second"]);
+            assert_eq!(NbtPath::parse("\"odd key\"").unwrap().get(&root).len(), 1);
+        }
+
+        #[test]
+        fn unmatched_paths_are_empty() {
+            let root = item_data();
+            for path in [
+                "display.Lore[2]",
+                "display.Lore[-3]",
+                "display.Name",
+                "missing.Lore[]",
+                "display[0]",
+                "Items.id",
+            ] {
+                assert!(
+                    NbtPath::parse(path).unwrap().get(&root).is_empty(),
+                    "{path}"
+                );
+            }
+        }
+
+        #[test]
+        fn parse_paths() {
+            assert_eq!(
+                NbtPath::parse("Items[].tag[3]").unwrap().nodes(),
+                [
+                    PathNode::Key("Items".to_string()),
+                    PathNode::All,
+                    PathNode::Key("tag".to_string()),
+                    PathNode::Index(3),
+                ]
+            );
+
+            for path in ["", "a.", "a[", "a[x]", "a..b", "'a", "a]", "a b", "a{}"] {
+                assert!(NbtPath::parse(path).is_err(), "{path}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_getters_read_back() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i8("byte", -3);
+        nbt.insert_i16("short", 300);
+        nbt.insert_i32("int", 70000);
+        nbt.insert_i64("long", 1 << 40);
+        nbt.insert_f32("float", 1.5);
+        nbt.insert_f64("double", -2.25);
+        nbt.insert_bool("bool", true);
+
+        assert_eq!(nbt.get_i8("byte"), Some(-3));
+        assert_eq!(nbt.get_i16("short"), Some(300));
+        assert_eq!(nbt.get_i32("int"), Some(70000));
+        assert_eq!(nbt.get_i64("long"), Some(1 << 40));
+        assert_eq!(nbt.get_f32("float"), Some(1.5));
+        assert_eq!(nbt.get_f64("double"), Some(-2.25));
+        assert_eq!(nbt.get_bool("bool"), Some(true));
+    }
+
+    #[test]
+    fn typed_getters_are_exact() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i32("int", 1);
+        nbt.insert_f64("double", 1.0);
+
+        assert_eq!(nbt.get_i8("int"), None);
+        assert_eq!(nbt.get_i64("int"), None);
+        assert_eq!(nbt.get_f64("int"), None);
+        assert_eq!(nbt.get_i32("double"), None);
+        assert_eq!(nbt.get_bool("int"), None);
+    }
+
+    #[test]
+    fn typed_lists_round_trip() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i32_list("ints", &[1, -2, 3]);
+        nbt.insert_i64_list("longs", &[1 << 40]);
+        nbt.insert_str_list("strs", &["a", "b"]);
+        let mut inner = NbtCompound::new();
+        inner.insert_i8("x", 1);
+        nbt.insert_compound_list("compounds", vec![inner.clone()]);
+        nbt.insert_i32_slice("array", &[4, 5]);
+
+        assert_eq!(nbt.get_i32_list("ints"), Some(vec![1, -2, 3]));
+        assert_eq!(nbt.get_i32_list("longs"), None);
+        assert_eq!(nbt.get_i32_list("array"), None);
+        assert_eq!(nbt.get_i32_slice("array"), Some(&[4, 5][..]));
+        assert_eq!(
+            nbt.get_slice("strs"),
+            Some(
+                &[
+                    NbtElement::String("a".to_string()),
+                    NbtElement::String("b".to_string())
+                ][..]
+            )
+        );
+        assert_eq!(
+            nbt.get_slice("compounds"),
+            Some(&[NbtElement::Compound(inner)][..])
+        );
+        assert_eq!(nbt.get_type("ints"), Some(NbtType::List));
+        assert_eq!(nbt.get_type("array"), Some(NbtType::IntArray));
+    }
+
+    fn read_err(bytes: &[u8]) -> NbtError {
+        NbtReader::new(bytes).read_named().unwrap_err()
+    }
+
+    #[test]
+    fn named_round_trip() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_str("name", "\u{1F600} nul\0");
+        nbt.insert_i64_slice("longs", &[1, -1]);
+        nbt.insert_list("empty", Vec::new());
+        let mut inner = NbtCompound::new();
+        inner.insert_f32("f", 0.5);
+        nbt.insert_compound_list("inner", vec![inner]);
+
+        let mut bytes = Vec::new();
+        write_named("root", &nbt, &mut bytes).unwrap();
+        let (name, read) = read_named(bytes.as_slice()).unwrap();
+        assert_eq!(name, "root");
+        assert_eq!(read, nbt);
+    }
+
+    #[test]
+    fn nested_compound_round_trip() {
+        let mut leaf = NbtCompound::new();
+        leaf.insert_i8_slice("bytes", &[1, -1]);
+        leaf.insert_i32_slice("ints", &[i32::MIN, i32::MAX]);
+        leaf.insert_i64_slice("longs", &[i64::MIN]);
+        leaf.insert_f64("double", -0.25);
+        let mut middle = NbtCompound::new();
+        middle.insert_compound("leaf", leaf.clone());
+        middle.insert_compound_list("leaves", vec![leaf.clone(), leaf]);
+        middle.insert_list("empty", Vec::new());
+        let mut nbt = NbtCompound::new();
+        nbt.insert_compound("middle", middle);
+        nbt.insert_str("text", "stone");
+
+        let mut bytes = Vec::new();
+        write_named("level", &nbt, &mut bytes).unwrap();
+        // the root type, then the u16 length prefixed name
+        assert_eq!(&bytes[..8], &[10, 0, 5, b'l', b'e', b'v', b'e', b'l']);
+        assert_eq!(bytes.last(), Some(&0));
+        assert_eq!(
+            read_named(bytes.as_slice()).unwrap(),
+            ("level".to_string(), nbt)
+        );
+    }
+
+    #[test]
+    fn truncated_input() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i32_slice("ints", &[1, 2, 3]);
+        let mut bytes = Vec::new();
+        write_named("", &nbt, &mut bytes).unwrap();
+
+        for len in 0..bytes.len() {
+            assert!(matches!(read_err(&bytes[..len]), NbtError::UnexpectedEnd));
+        }
+        let err = read_named(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(matches!(read_err(&[8, 0, 0]), NbtError::InvalidType(8)));
+        assert!(matches!(
+            read_err(&[10, 0, 0, 13, 0, 0]),
+            NbtError::InvalidType(13)
+        ));
+        assert!(matches!(
+            read_err(&[10, 0, 1, 0xff, 0]),
+            NbtError::Utf8(bytes) if bytes == [0xff]
+        ));
+        // a list of end elements with a length
+        assert!(matches!(
+            read_err(&[10, 0, 0, 9, 0, 0, 0, 0, 0, 0, 1, 0]),
+            NbtError::InvalidType(0)
+        ));
+        assert!(matches!(
+            read_err(&[10, 0, 0, 7, 0, 0, 0xff, 0xff, 0xff, 0xff, 0]),
+            NbtError::SizeLimit
+        ));
+    }
+
+    #[test]
+    fn claimed_max_length_fails_without_allocating() {
+        let max = i32::MAX.to_be_bytes();
+        // a byte list, a byte array and a long array claiming i32::MAX values
+        for prefix in [
+            &[10, 0, 0, 9, 0, 0, 1][..],
+            &[10, 0, 0, 7, 0, 0],
+            &[10, 0, 0, 12, 0, 0],
+        ] {
+            let mut bytes = prefix.to_vec();
+            bytes.extend(max);
+            bytes.extend([1, 2, 3]);
+            assert!(matches!(read_err(&bytes), NbtError::UnexpectedEnd));
+            assert!(read_named_with_quota(bytes.as_slice(), 1 << 20).is_err());
+        }
+    }
+
+    #[test]
+    fn depth_limit() {
+        let nested = |depth: usize| {
+            let mut bytes = vec![10, 0, 0, 9, 0, 0];
+            for _ in 1..depth {
+                bytes.extend([9, 0, 0, 0, 1]);
+            }
+            bytes.extend([0, 0, 0, 0, 0]);
+            bytes.push(0);
+            bytes
+        };
+
+        // the root compound is the first level
+        assert!(NbtReader::new(nested(MAX_DEPTH - 1).as_slice())
+            .read_named()
+            .is_ok());
+        assert!(matches!(read_err(&nested(MAX_DEPTH)), NbtError::DepthLimit));
+    }
+
+    #[test]
+    fn deeply_nested_compounds_fail() {
+        let mut nbt = NbtCompound::new();
+        for _ in 0..600 {
+            let mut outer = NbtCompound::new();
+            outer.insert_compound("inner", nbt);
+            nbt = outer;
+        }
+        let mut bytes = Vec::new();
+        write_named("", &nbt, &mut bytes).unwrap();
+
+        assert!(matches!(read_err(&bytes), NbtError::DepthLimit));
+        let err = read_named(bytes.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn limited_compound_put() {
+        let mut compound = LimitedNbtCompound::with_capacity_limit(2);
+        assert!(compound.put("a", NbtElement::Byte(1)).unwrap().is_none());
+        assert!(compound.put("b", NbtElement::Byte(2)).is_ok());
+        assert!(matches!(
+            compound.put("c", NbtElement::Byte(3)),
+            Err(NbtError::SizeLimit)
+        ));
+        // replacing is allowed at the cap
+        assert_eq!(
+            compound.put("a", NbtElement::Byte(4)).unwrap(),
+            Some(NbtElement::Byte(1))
+        );
+        assert_eq!(compound.len(), 2);
+        assert_eq!(LimitedNbtCompound::new().limit(), None);
+    }
+
+    #[test]
+    fn read_entry_limit() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i8("a", 1);
+        let mut inner = NbtCompound::new();
+        inner.insert_i8("b", 1);
+        inner.insert_i8("c", 1);
+        nbt.insert_compound("inner", inner);
+        let mut bytes = Vec::new();
+        write_named("", &nbt, &mut bytes).unwrap();
+
+        assert_eq!(read_named_with_limit(bytes.as_slice(), 2).unwrap().1, nbt);
+        let err = read_named_with_limit(bytes.as_slice(), 1).unwrap_err();
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref::<NbtError>()),
+            Some(NbtError::SizeLimit)
+        ));
+    }
+
+    #[test]
+    fn read_quota() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_str("name", "stone");
+        nbt.insert_i64_slice("longs", &[1, 2]);
+        nbt.insert_i32_list("ints", &[1, 2, 3]);
+        nbt.insert_f64("double", 0.5);
+        let mut inner = NbtCompound::new();
+        inner.insert_i16("s", 1);
+        nbt.insert_compound("inner", inner);
+        let mut bytes = Vec::new();
+        write_named("", &nbt, &mut bytes).unwrap();
+
+        let size = NbtElement::Compound(nbt.clone()).size_in_bytes();
+        assert_eq!(
+            read_named_with_quota(bytes.as_slice(), size).unwrap().1,
+            nbt
+        );
+        assert!(read_named_with_quota(bytes.as_slice(), size - 1).is_err());
+
+        // a huge array length fails before being read
+        let huge = [10, 0, 0, 11, 0, 0, 0x7f, 0xff, 0xff, 0xff];
+        let err = read_named_with_quota(huge.as_slice(), 1 << 20).unwrap_err();
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref::<NbtError>()),
+            Some(NbtError::SizeLimit)
+        ));
+    }
+
+    #[test]
+    fn tight_quota_rejects_large_array() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i32_slice("ints", &[7; 4096]);
+        let mut bytes = Vec::new();
+        write_named("", &nbt, &mut bytes).unwrap();
+
+        let err = read_named_with_quota(bytes.as_slice(), 1024).unwrap_err();
+        assert_eq!(err.to_string(), "Nbt size limit exceeded");
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref::<NbtError>()),
+            Some(NbtError::SizeLimit)
+        ));
+        // zero is unlimited
+        assert_eq!(read_named_with_quota(bytes.as_slice(), 0).unwrap().1, nbt);
+    }
+
+    #[test]
+    fn heterogeneous_list() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_list("mixed", vec![NbtElement::Int(1), NbtElement::Long(1)]);
+        let err = write_named("", &nbt, &mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref::<NbtError>()),
+            Some(NbtError::HeterogeneousList)
+        ));
+    }
+
+    #[test]
+    fn lenient_getters_coerce() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i32("int", 300);
+        nbt.insert_f64("double", -2.75);
+        nbt.insert_i16("short", 7);
+        nbt.insert_str("str", "7");
+
+        assert_eq!(nbt.get_i8_lenient("int"), Some(300i32 as i8));
+        assert_eq!(nbt.get_i64_lenient("int"), Some(300));
+        assert_eq!(nbt.get_i32_lenient("double"), Some(-3));
+        assert_eq!(nbt.get_i64_lenient("double"), Some(-3));
+        assert_eq!(nbt.get_i8_lenient("double"), Some(-3));
+        assert_eq!(nbt.get_f32_lenient("double"), Some(-2.75));
+        assert_eq!(nbt.get_f64_lenient("short"), Some(7.0));
+        assert_eq!(nbt.get_i32_lenient("str"), None);
+        assert_eq!(nbt.get_number("short"), Some(7.0));
+        assert_eq!(nbt.get_number("str"), None);
+    }
+
+    #[test]
+    fn type_id_round_trip() {
+        for id in 0..=12 {
+            let ty = NbtType::from_id(id).unwrap();
+            assert_eq!(ty.id(), id);
+            assert_eq!(NbtType::from_id(ty.id()), Some(ty));
+        }
+        assert_eq!(NbtType::from_id(13), None);
+        assert_eq!(NbtType::from_id(255), None);
+
+        let element = NbtElement::IntArray(IntArray::new(vec![1]));
+        assert_eq!(element.get_nbt_type(), NbtType::IntArray);
+        assert_eq!(element.get_type(), 11);
+        assert_eq!(NbtElement::Compound(NbtCompound::new()).get_type(), 10);
+    }
+
+    fn stable_hash(compound: &NbtCompound) -> u64 {
+        use std::hash::Hasher;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        compound.hash_stable(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn stable_hash_ignores_order() {
+        let keys = (0..32).map(|e| format!("key{e}")).collect::<Vec<_>>();
+        let mut a = NbtCompound::with_capacity(1);
+        let mut b = NbtCompound::with_capacity(256);
+        for (i, key) in keys.iter().enumerate() {
+            a.insert_f64(key, i as f64);
+        }
+        for (i, key) in keys.iter().enumerate().rev() {
+            b.insert_f64(key, i as f64);
+        }
+        let mut inner = NbtCompound::new();
+        inner.insert_f32("nan", f32::NAN);
+        a.insert_compound("inner", inner.clone());
+        b.insert_compound("inner", inner);
+
+        assert_eq!(stable_hash(&a), stable_hash(&b));
+        let set = [HashableNbtCompound(a.clone()), HashableNbtCompound(b)]
+            .into_iter()
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(set.len(), 1);
+
+        b = a.clone();
+        b.insert_f64("key0", -0.0);
+        assert_ne!(stable_hash(&a), stable_hash(&b));
+    }
+
+    #[test]
+    fn approx_eq_tolerates_rounding_and_nan() {
+        let near = |a: f64, b: f64| NbtElement::Double(a).approx_eq(&NbtElement::Double(b), 1e-9);
+        assert!(near(0.1 + 0.2, 0.3));
+        assert!(!near(0.1, 0.2));
+        assert!(near(f64::NAN, f64::NAN));
+        assert!(!near(f64::NAN, 0.0));
+        assert!(NbtElement::Float(f32::NAN).approx_eq(&NbtElement::Float(f32::NAN), 0.0));
+        assert!(!NbtElement::Float(1.0).approx_eq(&NbtElement::Double(1.0), 1.0));
+
+        let mut a = NbtCompound::new();
+        a.insert_list(
+            "list",
+            vec![NbtElement::Double(0.1 + 0.2), NbtElement::Float(f32::NAN)],
+        );
+        a.insert_f64("value", 1.0);
+        let mut b = NbtCompound::new();
+        b.insert_list(
+            "list",
+            vec![NbtElement::Double(0.3), NbtElement::Float(f32::NAN)],
+        );
+        b.insert_f64("value", 1.0 + 1e-12);
+
+        let (a, b) = (NbtElement::Compound(a), NbtElement::Compound(b));
+        assert!(a != b);
+        assert!(a.approx_eq(&b, 1e-9));
+
+        let mut c = NbtCompound::new();
+        c.insert_f64("value", 1.0);
+        assert!(!a.approx_eq(&NbtElement::Compound(c), 1e-9));
+    }
+
+    #[test]
+    fn heap_size_grows_with_content() {
+        assert_eq!(NbtElement::Int(1).heap_size(), 0);
+        assert_eq!(
+            NbtElement::String(String::with_capacity(10)).heap_size(),
+            10
+        );
+        assert_eq!(NbtElement::List(Vec::new()).heap_size(), 0);
+
+        let mut small = NbtCompound::new();
+        small.insert_i32("a", 1);
+
+        let mut large = NbtCompound::new();
+        for i in 0..64 {
+            large.insert_str(&format!("key{i}"), &"value".repeat(8));
+        }
+        large.insert_list("list", (0..64).map(NbtElement::Int).collect());
+
+        let small = NbtElement::Compound(small).heap_size();
+        let large = NbtElement::Compound(large).heap_size();
+        assert!(small > 0);
+        // at least the 64 strings of 40 bytes and 64 list elements
+        assert!(large > small + 64 * 40 + 64 * std::mem::size_of::<NbtElement>());
+    }
+
+    #[test]
+    fn numbers_widen_to_f64() {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i16("short", -300);
+        nbt.insert_f32("float", 0.25);
+        nbt.insert_i64("long", 1 << 40);
+        nbt.insert_str("string", "1");
+
+        assert_eq!(nbt.get_number("short"), Some(-300.0));
+        assert_eq!(nbt.get_number("float"), Some(0.25));
+        assert_eq!(nbt.get_number("long"), Some((1i64 << 40) as f64));
+        assert_eq!(nbt.get_number("string"), None);
+        assert_eq!(nbt.get_number("missing"), None);
+
+        assert_eq!(nbt.get_number_or("short", 1.0), -300.0);
+        assert_eq!(nbt.get_number_or("string", 1.0), 1.0);
+        assert_eq!(nbt.get_number_or("missing", 2.5), 2.5);
+    }
+
+    #[test]
+    fn snbt_display() {
+        let mut inner = NbtCompound::new();
+        inner.insert_i16("lvl", 2);
+        inner.insert_str("id", "minecraft:sharpness");
+
+        let mut nbt = NbtCompound::new();
+        nbt.insert_list(
+            "Enchantments",
+            vec![
+                NbtElement::Compound(inner),
+                NbtElement::Compound(NbtCompound::new()),
+            ],
+        );
+        nbt.insert_i8_slice("bytes", &[1, -2]);
+        nbt.insert_i64("long", 3);
+        nbt.insert_f32("float", 4.0);
+        nbt.insert_f64("double", 0.1);
+        nbt.insert_i8("Count", 1);
+        nbt.insert_str("my key", "say \"hi\"");
+        nbt.insert_i32_slice("ints", &[]);
+
+        assert_eq!(
+            Snbt(&NbtElement::Compound(nbt)).to_string(),
+            "{Count:1b,Enchantments:[{id:\"minecraft:sharpness\",lvl:2s},{}],\
+            bytes:[B;1b,-2b],double:0.1d,float:4.0f,ints:[I;],long:3L,\
+            \"my key\":'say \"hi\"'}"
+        );
+
+        assert_eq!(
+            Snbt(&NbtElement::String("it's \\".to_string())).to_string(),
+            "\"it's \\\\\""
+        );
+        assert_eq!(
+            Snbt(&NbtElement::LongArray(LongArray::new(vec![5, -6]))).to_string(),
+            "[L;5L,-6L]"
+        );
+    }
+
+    #[test]
+    fn compressed_round_trip() {
+        let mut data = NbtCompound::new();
+        data.insert_i64("Time", 123456);
+        data.insert_str("LevelName", "world");
+        let mut nbt = NbtCompound::new();
+        nbt.insert("Data".to_string(), NbtElement::Compound(data));
+
+        let mut bytes = Vec::new();
+        write_compressed("", &nbt, &mut bytes).unwrap();
+        // the gzip magic number
+        assert_eq!(&bytes[..2], [0x1f, 0x8b]);
+
+        let (name, read) = read_compressed(bytes.as_slice()).unwrap();
+        assert_eq!(name, "");
+        assert_eq!(read, nbt);
+
+        // uncompressed input is rejected
+        let mut raw = Vec::new();
+        write_named("", &nbt, &mut raw).unwrap();
+        assert!(read_compressed(raw.as_slice()).is_err());
+    }
+
+    #[test]
+    fn size_in_bytes_by_hand() {
+        assert_eq!(NbtElement::Int(1).size_in_bytes(), 12);
+        assert_eq!(NbtElement::Double(1.0).size_in_bytes(), 16);
+        assert_eq!(NbtElement::String("ab".to_string()).size_in_bytes(), 40);
+        assert_eq!(
+            NbtElement::IntArray(IntArray::new(vec![1, 2, 3])).size_in_bytes(),
+            24 + 12
+        );
+        assert_eq!(
+            NbtElement::LongArray(LongArray::new(vec![1])).size_in_bytes(),
+            24 + 8
+        );
+        // 37 + 2 * (4 + 9)
+        assert_eq!(
+            NbtElement::List(vec![NbtElement::Byte(0), NbtElement::Byte(1)]).size_in_bytes(),
+            63
+        );
+
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i16("a", 1);
+        nbt.insert_i8_slice("bc", &[1, 2]);
+        // 48 + (28 + 2 + 36 + 10) + (28 + 4 + 36 + 26)
+        assert_eq!(NbtElement::Compound(nbt.clone()).size_in_bytes(), 218);
+
+        let mut outer = NbtCompound::new();
+        outer.insert("x".to_string(), NbtElement::Compound(nbt));
+        // 48 + (28 + 2 + 36 + 218)
+        assert_eq!(NbtElement::Compound(outer).size_in_bytes(), 332);
+    }
+
+    #[test]
+    fn copy_from_merges_nested_compounds() {
+        let mut display = NbtCompound::new();
+        display.insert_str("Name", "old");
+        display.insert_i32("color", 1);
+        let mut nbt = NbtCompound::new();
+        nbt.insert("display".to_string(), NbtElement::Compound(display));
+        nbt.insert_i32("Damage", 3);
+        nbt.insert_i32_list("list", &[1, 2, 3]);
+        nbt.insert_str("kept", "yes");
+
+        let mut display = NbtCompound::new();
+        display.insert_str("Name", "new");
+        display.insert_str_list("Lore", &["line"]);
+        let mut other = NbtCompound::new();
+        other.insert("display".to_string(), NbtElement::Compound(display));
+        other.insert_i32("Damage", 5);
+        other.insert_i32_list("list", &[4]);
+        other.insert_i32("added", 1);
+
+        nbt.copy_from(&other);
+        let display = nbt.get_compound("display").unwrap();
+        assert_eq!(display.get_str("Name"), Some("new"));
+        assert_eq!(display.get_i32("color"), Some(1));
+        assert_eq!(
+            display.get("Lore"),
+            other.get_compound("display").unwrap().get("Lore")
+        );
+        assert_eq!(nbt.get_i32("Damage"), Some(5));
+        assert_eq!(nbt.get_i32_list("list"), Some(vec![4]));
+        assert_eq!(nbt.get_str("kept"), Some("yes"));
+        assert_eq!(nbt.get_i32("added"), Some(1));
+
+        // a compound replaces a scalar of the same key wholesale
+        let mut scalar = NbtCompound::new();
+        scalar.insert_i32("display", 0);
+        scalar.copy_from(&other);
+        assert_eq!(
+            scalar.get_compound("display"),
+            other.get_compound("display")
+        );
+    }
+
+    #[test]
+    fn prefix_keys_and_bulk_removal() {
+        let mut nbt = NbtCompound::new();
+        for key in ["mymod:a", "mymod:b", "other:c"] {
+            nbt.insert_i32(key, 1);
+        }
+
+        let mut keys = nbt.keys_matching("mymod:");
+        keys.sort_unstable();
+        assert_eq!(keys, ["mymod:a", "mymod:b"]);
+        assert!(nbt.keys_matching("missing:").is_empty());
+
+        nbt.retain(|key, _| !key.starts_with("mymod:"));
+        assert_eq!(nbt.keys_matching(""), ["other:c"]);
+
+        nbt.clear();
+        assert!(nbt.keys_matching("").is_empty());
+    }
+
+    #[test]
+    fn put_checked_rejects_type_changes() {
+        let mut nbt = NbtCompound::new();
+        assert!(nbt
+            .put_checked("value", NbtElement::Int(1))
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            nbt.put_checked("value", NbtElement::Int(2)).unwrap(),
+            Some(NbtElement::Int(1))
+        );
+
+        assert!(matches!(
+            nbt.put_checked("value", NbtElement::String("2".to_string())),
+            Err(NbtError::TypeMismatch {
+                expected: NbtType::Int,
+                found: NbtType::String,
+            })
+        ));
+        assert_eq!(nbt.get("value"), Some(&NbtElement::Int(2)));
+    }
+
+    #[test]
+    fn compound_json_round_trip() {
+        let mut inner = NbtCompound::new();
+        inner.insert_str("name", "stone");
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i64("long", -1 << 40);
+        nbt.insert_f64("double", 0.25);
+        nbt.insert_compound("inner", inner);
+        nbt.insert_list(
+            "list",
+            vec![
+                NbtElement::String("a".into()),
+                NbtElement::String("b".into()),
+            ],
+        );
+
+        let json = serde_json::to_string(&nbt).unwrap();
+        assert_eq!(serde_json::from_str::<NbtCompound>(&json).unwrap(), nbt);
+
+        // json numbers don't carry nbt widths
+        let mut narrow = NbtCompound::new();
+        narrow.insert_i32("int", 3);
+        let json = serde_json::to_string(&narrow).unwrap();
+        assert_eq!(
+            serde_json::from_str::<NbtCompound>(&json)
+                .unwrap()
+                .get("int"),
+            Some(&NbtElement::Long(3))
+        );
     }
 }
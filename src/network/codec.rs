@@ -0,0 +1,147 @@
+use super::Encode;
+
+/// Codec for packet frames prefixed with their length as a
+/// [`crate::util::VarInt`], for tokio framed transports.
+#[derive(Clone, Copy, Debug)]
+pub struct MinecraftCodec {
+    max_frame_len: usize,
+}
+
+impl MinecraftCodec {
+    /// Default max length of a frame, which fits in a 3-byte var int.
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 2097151;
+
+    pub fn new() -> Self {
+        Self {
+            max_frame_len: Self::DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Creates a codec rejecting frames longer than `max_frame_len`.
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self { max_frame_len }
+    }
+
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+}
+
+impl Default for MinecraftCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl tokio_util::codec::Decoder for MinecraftCodec {
+    type Item = bytes::BytesMut;
+    type Error = anyhow::Error;
+
+    fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let mut len = 0_usize;
+        let mut header_len = 0;
+
+        // read the length without consuming, as the frame may be incomplete
+        loop {
+            let Some(byte) = src.get(header_len).copied() else {
+                return Ok(None);
+            };
+            len |= ((byte & 0x7f) as usize) << (header_len * 7);
+            header_len += 1;
+
+            if (byte & 0x80) == 0 {
+                break;
+            }
+
+            if header_len >= 5 {
+                return Err(anyhow::anyhow!("Frame length too big"));
+            }
+        }
+
+        if len > self.max_frame_len {
+            return Err(anyhow::anyhow!(
+                "Frame length {len} exceeds the limit {}",
+                self.max_frame_len
+            ));
+        }
+
+        if src.len() < header_len + len {
+            src.reserve(header_len + len - src.len());
+            return Ok(None);
+        }
+
+        let _ = src.split_to(header_len);
+        Ok(Some(src.split_to(len)))
+    }
+}
+
+impl tokio_util::codec::Encoder<bytes::Bytes> for MinecraftCodec {
+    type Error = anyhow::Error;
+
+    fn encode(&mut self, item: bytes::Bytes, dst: &mut bytes::BytesMut) -> Result<(), Self::Error> {
+        if item.len() > self.max_frame_len {
+            return Err(anyhow::anyhow!(
+                "Frame length {} exceeds the limit {}",
+                item.len(),
+                self.max_frame_len
+            ));
+        }
+
+        crate::util::VarInt(item.len() as i32).encode(dst)?;
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_split_frame() {
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let payload = bytes::Bytes::from((0..200).map(|e| e as u8).collect::<Vec<_>>());
+        let mut codec = MinecraftCodec::new();
+        let mut encoded = bytes::BytesMut::new();
+        codec.encode(payload.clone(), &mut encoded).unwrap();
+        codec
+            .encode(bytes::Bytes::from_static(b"next"), &mut encoded)
+            .unwrap();
+        // 200 takes a 2-byte var int
+        assert_eq!(&encoded[..2], [0xc8, 0x01]);
+
+        let mut src = bytes::BytesMut::new();
+        // a partial length header
+        src.extend_from_slice(&encoded[..1]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        src.extend_from_slice(&encoded[1..100]);
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert_eq!(src.len(), 100);
+
+        src.extend_from_slice(&encoded[100..]);
+        assert_eq!(codec.decode(&mut src).unwrap().unwrap(), payload);
+        assert_eq!(&codec.decode(&mut src).unwrap().unwrap()[..], b"next");
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn frame_length_limit() {
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let mut codec = MinecraftCodec::with_max_frame_len(4);
+        let mut dst = bytes::BytesMut::new();
+        assert!(codec
+            .encode(bytes::Bytes::from_static(b"12345"), &mut dst)
+            .is_err());
+        assert!(dst.is_empty());
+
+        let mut src = bytes::BytesMut::from(&[5, 1, 2][..]);
+        assert!(codec.decode(&mut src).is_err());
+
+        // var ints longer than 5 bytes
+        let mut src = bytes::BytesMut::from(&[0xff; 6][..]);
+        assert!(MinecraftCodec::new().decode(&mut src).is_err());
+    }
+}
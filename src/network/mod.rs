@@ -1,5 +1,9 @@
+mod codec;
+
 use crate::prelude::*;
 
+pub use codec::*;
+
 /// Describes types that can be encoded into a packet buffer.
 pub trait Encode {
     /// Encode into a buffer.
@@ -20,12 +24,171 @@ pub trait Decode<'de> {
         B: bytes::Buf;
 }
 
+/// Read-only helpers for inspecting upcoming bytes of a packet buffer
+/// without advancing it, for debugging malformed packets.
+///
+/// This is implemented for all [`bytes::Buf`]s, including
+/// non-contiguous ones like [`bytes::buf::Chain`].
+pub trait Inspect: bytes::Buf {
+    /// Returns the next byte without consuming it.
+    ///
+    /// [`bytes::Buf::chunk`] is only empty if nothing remains,
+    /// so the first chunk always holds the next byte.
+    fn peek_u8(&self) -> Option<u8> {
+        self.chunk().first().copied()
+    }
+
+    /// Returns a hex dump of the remaining bytes for logging,
+    /// with 16 bytes per line prefixed by their offsets.
+    ///
+    /// All chunks exposed by [`bytes::Buf::chunks_vectored`] are dumped.
+    /// Buffers which only expose their first chunk that way end the
+    /// dump with the count of bytes not shown.
+    fn to_hex_dump(&self) -> String {
+        let mut slices = vec![std::io::IoSlice::new(&[]); 4];
+        let chunks = loop {
+            let len = self.chunks_vectored(&mut slices);
+            if len < slices.len() {
+                break &slices[..len];
+            }
+            slices = vec![std::io::IoSlice::new(&[]); slices.len() * 2];
+        };
+
+        let mut string = String::new();
+        let bytes = chunks.iter().flat_map(|e| e.iter());
+        let mut shown = 0;
+        for (i, byte) in bytes.enumerate() {
+            if i % 16 == 0 {
+                if i > 0 {
+                    string.push('\n');
+                }
+                string.push_str(&format!("{i:08x}:"));
+            }
+            string.push_str(&format!(" {byte:02x}"));
+            shown += 1;
+        }
+
+        if shown < self.remaining() {
+            if shown > 0 {
+                string.push('\n');
+            }
+            string.push_str(&format!("... {} more bytes", self.remaining() - shown));
+        }
+
+        string
+    }
+}
+
+impl<T: bytes::Buf + ?Sized> Inspect for T {}
+
 /// Layer for encoding and decoding in nbt binary format for packets.
 pub struct Nbt<'a, T>(pub &'a T);
 
 /// Layer for encoding and decoding in json utf8 for packets.
 pub struct Json<'a, T>(pub &'a T);
 
+/// Layer for encoding and decoding rotation angles in degrees,
+/// as a single byte representing 1/256 of a full turn.
+pub struct Angle(pub f32);
+
+/// Encode a sequence prefixed with its length as a [`crate::util::VarInt`],
+/// writing each element with the given function.
+pub fn encode_seq<T, B, F>(buf: &mut B, items: &[T], mut write: F) -> anyhow::Result<()>
+where
+    B: bytes::BufMut,
+    F: FnMut(&mut B, &T) -> anyhow::Result<()>,
+{
+    crate::util::VarInt(items.len() as i32).encode(buf)?;
+
+    for item in items.iter() {
+        write(buf, item)?;
+    }
+
+    Ok(())
+}
+
+/// Decode a sequence prefixed with its length as a [`crate::util::VarInt`],
+/// reading each element with the given function.
+///
+/// Lengths larger than `max` are rejected before allocating, and the
+/// pre-allocation is bounded by the remaining bytes of the buffer.
+pub fn decode_seq<T, B, F>(buf: &mut B, max: usize, mut read: F) -> anyhow::Result<Vec<T>>
+where
+    B: bytes::Buf,
+    F: FnMut(&mut B) -> anyhow::Result<T>,
+{
+    let len = crate::util::VarInt::decode(buf)?;
+    if len < 0 || len as usize > max {
+        return Err(anyhow::anyhow!(
+            "Sequence length {len} out of bounds 0..={max}"
+        ));
+    }
+
+    let len = len as usize;
+    let mut vec = Vec::with_capacity(len.min(buf.remaining()));
+
+    for _ in 0..len {
+        vec.push(read(buf)?);
+    }
+
+    Ok(vec)
+}
+
+/// Encode a coord as a fixed-point value with 12 fractional bits,
+/// as used by relative entity moves.
+fn encode_packet_coord(coord: f64) -> i64 {
+    (coord * 4096.0).round() as i64
+}
+
+fn delta_coords(old: glam::DVec3, new: glam::DVec3) -> [i64; 3] {
+    [
+        encode_packet_coord(new.x) - encode_packet_coord(old.x),
+        encode_packet_coord(new.y) - encode_packet_coord(old.y),
+        encode_packet_coord(new.z) - encode_packet_coord(old.z),
+    ]
+}
+
+/// Whether the move from `old` to `new` can be encoded by [`encode_delta_pos`],
+/// or an absolute position should be sent instead.
+pub fn can_use_delta(old: glam::DVec3, new: glam::DVec3) -> bool {
+    delta_coords(old, new)
+        .into_iter()
+        .all(|e| (i16::MIN as i64..=i16::MAX as i64).contains(&e))
+}
+
+/// Encode the move from `old` to `new` as three `i16` deltas
+/// in 1/4096 blocks.
+///
+/// Fails if the move is too far, see [`can_use_delta`].
+pub fn encode_delta_pos<B>(buf: &mut B, old: glam::DVec3, new: glam::DVec3) -> anyhow::Result<()>
+where
+    B: bytes::BufMut,
+{
+    for delta in delta_coords(old, new) {
+        i16::try_from(delta)
+            .map_err(|_| anyhow::anyhow!("Position delta {delta} too large"))?
+            .encode(buf)?;
+    }
+
+    Ok(())
+}
+
+/// Decode a position moved from `old` by deltas written with [`encode_delta_pos`].
+pub fn decode_delta_pos<B>(buf: &mut B, old: glam::DVec3) -> anyhow::Result<glam::DVec3>
+where
+    B: bytes::Buf,
+{
+    let mut decode = |coord: f64| -> anyhow::Result<f64> {
+        Ok((encode_packet_coord(coord) + i16::decode(buf)? as i64) as f64 / 4096.0)
+    };
+
+    Ok(glam::DVec3::new(
+        decode(old.x)?,
+        decode(old.y)?,
+        decode(old.z)?,
+    ))
+}
+
 mod packet_buf_impl {
     use std::{hash::Hash, ops::Deref};
 
@@ -302,7 +465,7 @@ mod packet_buf_impl {
         where
             B: bytes::Buf,
         {
-            Ok(buf.get_u8() == 0)
+            Ok(buf.get_u8() != 0)
         }
     }
 
@@ -370,6 +533,28 @@ mod packet_buf_impl {
         }
     }
 
+    impl Encode for Angle {
+        fn encode<B>(&self, buf: &mut B) -> anyhow::Result<()>
+        where
+            B: bytes::BufMut,
+        {
+            // wraps around, so 360 and 0 are both encoded to 0
+            buf.put_u8((self.0 / 360.0 * 256.0).floor() as i32 as u8);
+            Ok(())
+        }
+    }
+
+    impl<'de> Decode<'de> for Angle {
+        type Output = f32;
+
+        fn decode<B>(buf: &'de mut B) -> anyhow::Result<Self::Output>
+        where
+            B: bytes::Buf,
+        {
+            Ok(buf.get_u8() as f32 * 360.0 / 256.0)
+        }
+    }
+
     impl Encode for crate::util::VarInt {
         fn encode<B>(&self, buf: &mut B) -> anyhow::Result<()>
         where
@@ -501,13 +686,7 @@ mod packet_buf_impl {
         where
             B: bytes::BufMut,
         {
-            crate::util::VarInt(self.len() as i32).encode(buf)?;
-
-            for object in self.iter() {
-                object.encode(buf)?;
-            }
-
-            Ok(())
+            encode_seq(buf, self, |buf, object| object.encode(buf))
         }
     }
 
@@ -521,14 +700,7 @@ mod packet_buf_impl {
         where
             B: bytes::Buf,
         {
-            let len = crate::util::VarInt::decode(buf)? as usize;
-            let mut vec = Vec::with_capacity(len);
-
-            for _ in 0..len {
-                vec.push(T::decode(buf)?);
-            }
-
-            Ok(vec)
+            decode_seq(buf, i32::MAX as usize, |buf| T::decode(buf))
         }
     }
 
@@ -809,7 +981,7 @@ mod packet_buf_impl {
             } else {
                 true.encode(buf)?;
                 self.item().encode(buf)?;
-                buf.put_u8(self.count);
+                buf.put_u8(self.count());
 
                 match self.nbt() {
                     Some(map) => {
@@ -837,8 +1009,18 @@ mod packet_buf_impl {
                 Ok(crate::item::ItemStack::default())
             } else {
                 let item = crate::item::Item::decode(buf)?;
-                let mut stack = crate::item::ItemStack::new(&item, buf.get_u8());
-                stack.set_nbt(Some(Nbt::decode(buf)?));
+                let count = buf.get_u8();
+                let mut stack = crate::item::ItemStack::new(&item, 0);
+
+                // an end tag is written if there is no nbt to sync
+                if buf.peek_u8() == Some(0) {
+                    buf.advance(1);
+                } else {
+                    stack.set_nbt(Some(Nbt::decode(buf)?));
+                }
+
+                // the max count may depend on the nbt
+                stack.set_count(count);
                 Ok(stack)
             }
         }
@@ -888,3 +1070,127 @@ mod packet_buf_impl {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Buf;
+
+    #[test]
+    fn inspect_without_consuming() {
+        let data = (0u8..20).collect::<Vec<_>>();
+        let mut buf = &data[..];
+
+        assert_eq!(buf.peek_u8(), Some(0));
+        assert_eq!(buf.remaining(), 20);
+        assert_eq!(
+            buf.to_hex_dump(),
+            "00000000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n00000010: 10 11 12 13"
+        );
+
+        buf.advance(18);
+        assert_eq!(buf.peek_u8(), Some(0x12));
+        assert_eq!(buf.remaining(), 2);
+        assert_eq!(buf.to_hex_dump(), "00000000: 12 13");
+
+        buf.advance(2);
+        assert_eq!(buf.peek_u8(), None);
+        assert_eq!(buf.to_hex_dump(), "");
+    }
+
+    #[test]
+    fn inspect_chained_buffers() {
+        let head = (0u8..10).collect::<Vec<_>>();
+        let tail = (10u8..20).collect::<Vec<_>>();
+        let mut buf = (&head[..]).chain(&tail[..]);
+
+        assert_eq!(
+            buf.to_hex_dump(),
+            "00000000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n00000010: 10 11 12 13"
+        );
+        buf.advance(10);
+        assert_eq!(buf.peek_u8(), Some(10));
+        assert_eq!(buf.to_hex_dump(), "00000000: 0a 0b 0c 0d 0e 0f 10 11 12 13");
+    }
+
+    #[test]
+    fn angle_round_trip() {
+        let encode = |degrees: f32| {
+            let mut buf = Vec::new();
+            Angle(degrees).encode(&mut buf).unwrap();
+            buf
+        };
+
+        assert_eq!(encode(0.0), [0]);
+        assert_eq!(encode(360.0), [0]);
+        assert_eq!(encode(-90.0), [192]);
+        assert_eq!(encode(450.0), [64]);
+
+        for degrees in [0.0f32, 45.0, 90.5, 179.0, 270.0, 359.0, -45.0] {
+            let buf = encode(degrees);
+            let decoded = Angle::decode(&mut &buf[..]).unwrap();
+            assert!((0.0..360.0).contains(&decoded));
+            let diff = (decoded - degrees).rem_euclid(360.0);
+            assert!(
+                diff.min(360.0 - diff) <= 360.0 / 256.0,
+                "{degrees} -> {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn seq_round_trip() {
+        let ids = vec![
+            Identifier::parse("minecraft:stone"),
+            Identifier::parse("mymod:gem"),
+            Identifier::parse("minecraft:apple"),
+        ];
+        let mut buf = Vec::new();
+        encode_seq(&mut buf, &ids, |buf, id| id.encode(buf)).unwrap();
+        assert_eq!(buf[0], 3);
+
+        let decoded = decode_seq(&mut &buf[..], 3, |buf| Identifier::decode(buf)).unwrap();
+        assert_eq!(decoded, ids);
+
+        let err = decode_seq(&mut &buf[..], 2, |buf| Identifier::decode(buf)).unwrap_err();
+        assert!(err.to_string().contains("out of bounds"));
+
+        // a negative length
+        let mut buf = Vec::new();
+        crate::util::VarInt(-1).encode(&mut buf).unwrap();
+        assert!(decode_seq(&mut &buf[..], 16, |buf| Identifier::decode(buf)).is_err());
+    }
+
+    #[test]
+    fn delta_pos_round_trip() {
+        let old = glam::DVec3::new(100.3, -60.123456, -2000.5);
+        for offset in [
+            glam::DVec3::ZERO,
+            glam::DVec3::new(0.001, -0.5, 3.25),
+            glam::DVec3::new(-7.99, 7.99, 0.0001),
+        ] {
+            let new = old + offset;
+            assert!(can_use_delta(old, new));
+
+            let mut buf = Vec::new();
+            encode_delta_pos(&mut buf, old, new).unwrap();
+            assert_eq!(buf.len(), 6);
+
+            let decoded = decode_delta_pos(&mut &buf[..], old).unwrap();
+            assert!((decoded - new).abs().max_element() <= 0.5 / 4096.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn delta_pos_too_far() {
+        let old = glam::DVec3::new(0.5, 64.0, 0.5);
+        for new in [
+            old + glam::DVec3::new(8.0, 0.0, 0.0),
+            old + glam::DVec3::new(0.0, -8.5, 0.0),
+            old + glam::DVec3::new(0.0, 0.0, 100.0),
+        ] {
+            assert!(!can_use_delta(old, new));
+            assert!(encode_delta_pos(&mut Vec::new(), old, new).is_err());
+        }
+    }
+}
@@ -19,6 +19,11 @@ impl<T> Holder<T> {
         &self.key
     }
 
+    /// The registered value of this holder.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
     /// If this registration is in target tag.
     pub fn is_in(&self, tag: &tag::TagKey<T>) -> bool {
         self.tags.read().contains(tag)
@@ -39,12 +44,19 @@ impl<T> Deref for Holder<T> {
 pub struct Registry<T> {
     default: Option<usize>,
     entries: Vec<Holder<T>>,
-    id_map: hashbrown::HashMap<Identifier, usize>,
+    /// Ids to raw ids, keyed by interned ids for O(1) comparisons.
+    id_map: hashbrown::HashMap<InternedId, usize>,
     /// Key of this registry.
     pub key: RegistryKey<Self>,
     key_map: hashbrown::HashMap<RegistryKey<T>, usize>,
+    /// Old ids to raw ids of their renamed entries.
+    aliases: hashbrown::HashMap<Identifier, usize>,
     /// Tag to entries mapping of this registry.
-    pub tags: parking_lot::RwLock<hashbrown::HashMap<tag::TagKey<T>, Vec<usize>>>,
+    ///
+    /// This is private so every binding also updates `tag_bits`.
+    tags: parking_lot::RwLock<hashbrown::HashMap<tag::TagKey<T>, Vec<usize>>>,
+    /// Bitsets of raw ids in each tag, as 64-bit words.
+    tag_bits: parking_lot::RwLock<hashbrown::HashMap<tag::TagKey<T>, Vec<u64>>>,
 }
 
 impl<T> Registry<T> {
@@ -55,7 +67,7 @@ impl<T> Registry<T> {
 
     /// Whether this registry contains an entry with the target id.
     pub fn contains_id(&self, id: &Identifier) -> bool {
-        self.id_map.contains_key(id)
+        InternedId::get(id).is_some_and(|e| self.id_map.contains_key(&e))
     }
 
     /// Returns the default entry of this reigstry.
@@ -65,10 +77,15 @@ impl<T> Registry<T> {
     /// Panic if a default entry don't exist.
     /// See [`Self::is_defaulted`].
     pub fn default_entry(&self) -> (usize, &Holder<T>) {
-        let def = self
-            .default
-            .expect("trying to get a default entry that don't exist");
-        (def, self.get_from_raw(def).unwrap())
+        self.try_default_entry()
+            .expect("trying to get a default entry that don't exist")
+    }
+
+    /// Returns the default entry of this registry,
+    /// or `None` if a default entry don't exist.
+    pub fn try_default_entry(&self) -> Option<(usize, &Holder<T>)> {
+        self.default
+            .and_then(|def| self.get_from_raw(def).map(|e| (def, e)))
     }
 
     /// Get an entry from a [`RegistryKey`].
@@ -79,9 +96,20 @@ impl<T> Registry<T> {
     }
 
     /// Get an entry from an [`Identifier`].
+    ///
+    /// Ids which are not interned can't be registered,
+    /// so this never interns the given id.
     pub fn get_from_id(&self, id: &Identifier) -> Option<(usize, &Holder<T>)> {
+        self.get_from_interned(InternedId::get(id)?)
+    }
+
+    /// Get an entry from an [`InternedId`].
+    ///
+    /// This skips hashing the full identifier, so prefer it
+    /// over [`Self::get_from_id`] when an interned id is at hand.
+    pub fn get_from_interned(&self, id: InternedId) -> Option<(usize, &Holder<T>)> {
         self.id_map
-            .get(id)
+            .get(&id)
             .map(|e| (*e, self.entries.get(*e).unwrap()))
     }
 
@@ -99,6 +127,152 @@ impl<T> Registry<T> {
     pub fn iter(&self) -> std::slice::Iter<'_, Holder<T>> {
         self.entries.iter()
     }
+
+    /// Bind the given tag to entries mapping into this registry,
+    /// replacing all existing tag bindings.
+    ///
+    /// Raw ids of each tag are sorted and deduplicated, so iterating
+    /// a tag is deterministic regardless of the binding order.
+    pub fn populate_tags(&self, tags: hashbrown::HashMap<tag::TagKey<T>, Vec<usize>>) {
+        self.reset_tags();
+        let mut map = self.tags.write();
+        let mut bits_map = self.tag_bits.write();
+
+        for (tag, mut ids) in tags.into_iter() {
+            ids.retain(|id| *id < self.entries.len());
+            ids.sort_unstable();
+            ids.dedup();
+
+            let mut bits = vec![0_u64; self.entries.len().div_ceil(64)];
+            for id in ids.iter() {
+                self.entries[*id].tags.write().push(tag.clone());
+                bits[id / 64] |= 1 << (id % 64);
+            }
+
+            bits_map.insert(tag.clone(), bits);
+            map.insert(tag, ids);
+        }
+    }
+
+    /// Tag to entries mapping of this registry, holding the read lock.
+    ///
+    /// Tags are bound through [`Self::populate_tags`] and [`Self::ensure_tag`].
+    pub fn tags(
+        &self,
+    ) -> parking_lot::RwLockReadGuard<'_, hashbrown::HashMap<tag::TagKey<T>, Vec<usize>>> {
+        self.tags.read()
+    }
+
+    /// Remove all tag bindings of this registry and its entries.
+    pub fn reset_tags(&self) {
+        self.tags.write().clear();
+        self.tag_bits.write().clear();
+        self.entries.iter().for_each(|e| e.tags.write().clear());
+    }
+
+    /// Whether the entry with the target raw id is in the tag.
+    ///
+    /// This looks up a bitset cached when binding tags,
+    /// so it's faster than [`Holder::is_in`] for hot checks.
+    pub fn is_in_tag(&self, raw_id: usize, tag: &tag::TagKey<T>) -> bool {
+        self.tag_bits
+            .read()
+            .get(tag)
+            .and_then(|bits| bits.get(raw_id / 64))
+            .is_some_and(|word| word & (1 << (raw_id % 64)) != 0)
+    }
+
+    /// Returns an iterator over entries in the target tag,
+    /// ordered by their raw ids.
+    pub fn iter_tag(&self, tag: &tag::TagKey<T>) -> impl Iterator<Item = (usize, &Holder<T>)> {
+        self.tag_entries(tag)
+            .into_iter()
+            .map(|id| (id, &self.entries[id]))
+    }
+
+    /// Returns a snapshot of raw ids in the target tag,
+    /// or an empty vec if the tag is not bound.
+    ///
+    /// This only takes the read lock of tags.
+    pub fn tag_entries(&self, tag: &tag::TagKey<T>) -> Vec<usize> {
+        self.tags.read().get(tag).cloned().unwrap_or_default()
+    }
+
+    /// Get raw id of the entry renamed from the old id.
+    pub fn get_from_alias(&self, id: &Identifier) -> Option<usize> {
+        self.aliases.get(id).copied()
+    }
+
+    /// Resolve raw ids referenced by an ingredient spec, which can be
+    /// an entry id, an aliased old id, or a tag id prefixed with `#`.
+    ///
+    /// Returns an empty vec if nothing is resolved.
+    pub fn resolve_ingredient_ids(&self, spec: &str) -> Vec<usize> {
+        if let Some(tag) = spec.strip_prefix('#') {
+            return Identifier::try_parse(tag).map_or_else(
+                |_| Vec::new(),
+                |id| self.tag_entries(&tag::TagKey::new(self.key.clone(), id)),
+            );
+        }
+
+        Identifier::try_parse(spec)
+            .ok()
+            .and_then(|id| {
+                self.get_from_id(&id)
+                    .map(|e| e.0)
+                    .or_else(|| self.get_from_alias(&id))
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Bind an empty entry for the target tag if it's not bound.
+    pub fn ensure_tag(&self, tag: &tag::TagKey<T>) {
+        if !self.tags.read().contains_key(tag) {
+            let mut map = self.tags.write();
+            let mut bits_map = self.tag_bits.write();
+            if !map.contains_key(tag) {
+                map.insert(tag.clone(), Vec::new());
+                bits_map.insert(tag.clone(), vec![0; self.entries.len().div_ceil(64)]);
+            }
+        }
+    }
+}
+
+impl<T: serde::Serialize> Registry<T> {
+    /// Hash the serialized form of each entry by its id,
+    /// for detecting value changes after reloading.
+    ///
+    /// Entries failed to serialize are skipped.
+    pub fn value_fingerprint(&self) -> hashbrown::HashMap<Identifier, u64> {
+        use std::hash::{Hash, Hasher};
+
+        self.entries
+            .iter()
+            .filter_map(|e| {
+                let bytes = serde_json::to_vec(&e.value).ok()?;
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                bytes.hash(&mut hasher);
+                Some((e.key.value().clone(), hasher.finish()))
+            })
+            .collect()
+    }
+}
+
+/// Ids of entries whose fingerprints differ between the two
+/// [`Registry::value_fingerprint`]s, including added and removed entries.
+pub fn diff_values(
+    old: &hashbrown::HashMap<Identifier, u64>,
+    new: &hashbrown::HashMap<Identifier, u64>,
+) -> Vec<Identifier> {
+    let mut ids = new
+        .iter()
+        .filter(|(id, value)| old.get(*id) != Some(value))
+        .map(|e| e.0.clone())
+        .chain(old.keys().filter(|id| !new.contains_key(*id)).cloned())
+        .collect::<Vec<_>>();
+    ids.sort_unstable_by_key(|e| e.to_string());
+    ids
 }
 
 impl<T> std::ops::Index<usize> for Registry<T> {
@@ -148,15 +322,38 @@ impl<T: PartialEq + Eq> crate::util::collections::Indexed<Holder<T>> for Registr
 /// Mutable registry builder for building [`Registry`].
 pub struct Builder<T: Registration> {
     entries: Vec<(T, Identifier)>,
+    hooks: Vec<BuildHook<T>>,
+    aliases: Vec<(Identifier, Identifier)>,
 }
 
+/// A hook called for each entry when building a registry,
+/// receiving the raw id, id and value of the entry.
+pub type BuildHook<T> = Box<dyn Fn(usize, &Identifier, &T) + Send + Sync>;
+
 impl<T: Registration> Builder<T> {
     pub const fn new() -> Self {
         Self {
             entries: Vec::new(),
+            hooks: Vec::new(),
+            aliases: Vec::new(),
         }
     }
 
+    /// Add an alias so that the `old` id resolves to the entry with the `new` id,
+    /// for entries that have been renamed.
+    pub fn alias(&mut self, old: Identifier, new: Identifier) {
+        self.aliases.push((old, new));
+    }
+
+    /// Add a hook to be called for each entry once the registry is built,
+    /// for populating derived indexes.
+    pub fn on_each<F>(&mut self, f: F)
+    where
+        F: Fn(usize, &Identifier, &T) + Send + Sync + 'static,
+    {
+        self.hooks.push(Box::new(f));
+    }
+
     /// Register a new value and its id into this builder and return its raw id.
     pub fn register(&mut self, value: T, id: Identifier) -> anyhow::Result<usize> {
         if self.entries.iter().any(|e| e.1 == id) {
@@ -166,6 +363,40 @@ impl<T: Registration> Builder<T> {
             Ok(self.entries.len() - 1)
         }
     }
+
+    /// Append all entries of another builder into this builder,
+    /// preserving their insertion order.
+    ///
+    /// Nothing is appended if any id of the other builder
+    /// already exists in this builder.
+    pub fn extend(&mut self, other: Builder<T>) -> anyhow::Result<()> {
+        if let Some(e) = other
+            .entries
+            .iter()
+            .find(|e| self.entries.iter().any(|ee| ee.1 == e.1))
+        {
+            Err(anyhow::anyhow!(
+                "Registration with id {} already exist!",
+                e.1
+            ))
+        } else {
+            self.entries.extend(other.entries);
+            self.hooks.extend(other.hooks);
+            self.aliases.extend(other.aliases);
+            Ok(())
+        }
+    }
+
+    /// Build an owned registry without going through a [`crate::util::Freezer`].
+    ///
+    /// This is useful for tooling which should not depend on global registries.
+    pub fn build_standalone(
+        self,
+        key: RegistryKey<Registry<T>>,
+        default: Option<Identifier>,
+    ) -> Registry<T> {
+        crate::util::Freeze::build(self, (key, default))
+    }
 }
 
 impl<T: Registration> crate::util::Freeze<Registry<T>> for Builder<T> {
@@ -178,6 +409,9 @@ impl<T: Registration> crate::util::Freeze<Registry<T>> for Builder<T> {
             .enumerate()
             .map(|mut e| {
                 e.1 .0.accept(e.0);
+                for hook in self.hooks.iter() {
+                    hook(e.0, &e.1 .1, &e.1 .0);
+                }
                 Holder {
                     value: e.1 .0,
                     key: RegistryKey::new(&opts.0, e.1 .1.clone()),
@@ -189,13 +423,16 @@ impl<T: Registration> crate::util::Freeze<Registry<T>> for Builder<T> {
         let id_map = {
             let mut map = hashbrown::HashMap::new();
             for e in entries.iter().enumerate() {
-                map.insert(e.1.key.value().clone(), e.0);
+                map.insert(e.1.key.value().clone().intern(), e.0);
             }
             map
         };
 
         Registry {
-            default: opts.1.map(|e| id_map.get(&e).copied()).flatten(),
+            default: opts
+                .1
+                .and_then(|e| InternedId::get(&e))
+                .and_then(|e| id_map.get(&e).copied()),
             key_map: {
                 let mut map = hashbrown::HashMap::new();
                 for e in entries.iter().enumerate() {
@@ -203,10 +440,20 @@ impl<T: Registration> crate::util::Freeze<Registry<T>> for Builder<T> {
                 }
                 map
             },
+            aliases: self
+                .aliases
+                .into_iter()
+                .filter_map(|(old, new)| {
+                    InternedId::get(&new)
+                        .and_then(|e| id_map.get(&e))
+                        .map(|e| (old, *e))
+                })
+                .collect(),
             entries,
             id_map,
             key: opts.0,
             tags: parking_lot::RwLock::new(hashbrown::HashMap::new()),
+            tag_bits: parking_lot::RwLock::new(hashbrown::HashMap::new()),
         }
     }
 }
@@ -260,13 +507,31 @@ impl<T> RegistryKey<T> {
 
     /// Value of this key.
     pub fn value(&self) -> &Identifier {
-        &self.inner.0
+        &self.inner.1
     }
 
     /// Registry of this key.
     pub fn reg(&self) -> &Identifier {
-        &self.inner.1
+        &self.inner.0
     }
+
+    /// Returns the canonical string form of this key,
+    /// in `registry#value` format.
+    ///
+    /// This can be parsed back with [`parse_registry_key`].
+    pub fn to_canonical_string(&self) -> String {
+        format!("{}#{}", self.inner.0, self.inner.1)
+    }
+}
+
+/// Parse the canonical string form of a registry key
+/// (see [`RegistryKey::to_canonical_string`]) into its
+/// registry id and value id.
+pub fn parse_registry_key(s: &str) -> anyhow::Result<(Identifier, Identifier)> {
+    let (reg, value) = s
+        .split_once('#')
+        .ok_or_else(|| anyhow::anyhow!("Missing '#' in registry key: {s}"))?;
+    Ok((Identifier::try_parse(reg)?, Identifier::try_parse(value)?))
 }
 
 impl<T> RegistryKey<Registry<T>> {
@@ -316,3 +581,396 @@ impl<T> std::hash::Hash for RegistryKey<T> {
 /// Freezeable registry for building and freezing registries,
 /// just like what MCJE's `Registry` do.
 pub type Freezer<T> = crate::util::Freezer<Registry<T>, Builder<T>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Entry(usize);
+
+    impl Registration for Entry {
+        fn accept(&mut self, id: usize) {
+            self.0 = id;
+        }
+
+        fn raw_id(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn registry(ids: &[&str]) -> Registry<Entry> {
+        let mut builder = Builder::new();
+        for id in ids {
+            builder.register(Entry(0), Identifier::parse(id)).unwrap();
+        }
+        builder.build_standalone(
+            RegistryKey::of_reg(Identifier::parse("test_entries")),
+            Some(Identifier::parse(ids[0])),
+        )
+    }
+
+    #[test]
+    fn id_lookups() {
+        let registry = registry(&["registry_test_a", "registry_test_b"]);
+        let id = Identifier::parse("registry_test_b");
+
+        let (raw, holder) = registry.get_from_id(&id).unwrap();
+        assert_eq!(raw, 1);
+        assert_eq!(holder.raw_id(), 1);
+        assert_eq!(
+            registry.get_from_interned(id.clone().intern()).unwrap().0,
+            raw
+        );
+        assert!(registry.contains_id(&id));
+        assert_eq!(registry.default_entry().0, 0);
+    }
+
+    #[test]
+    fn tag_bits_match_entries() {
+        let ids = (0..130)
+            .map(|e| format!("registry_test_tag_{e}"))
+            .collect::<Vec<_>>();
+        let registry = registry(&ids.iter().map(String::as_str).collect::<Vec<_>>());
+        let tag = |path: &str| tag::TagKey::new(registry.key.clone(), Identifier::parse(path));
+
+        let mut tags = hashbrown::HashMap::new();
+        tags.insert(tag("odd"), (1..130).step_by(2).collect::<Vec<_>>());
+        tags.insert(tag("edges"), vec![129, 0, 63, 64, 64, 200]);
+        registry.populate_tags(tags);
+        registry.ensure_tag(&tag("empty"));
+        registry.ensure_tag(&tag("odd"));
+
+        for path in ["odd", "edges", "empty", "unbound"] {
+            let tag = tag(path);
+            let entries = registry.tag_entries(&tag);
+            for raw in 0..registry.entries.len() {
+                assert_eq!(
+                    registry.is_in_tag(raw, &tag),
+                    entries.contains(&raw),
+                    "{path} {raw}"
+                );
+                assert_eq!(
+                    registry.is_in_tag(raw, &tag),
+                    registry.get_from_raw(raw).unwrap().is_in(&tag)
+                );
+            }
+        }
+
+        assert_eq!(registry.tag_entries(&tag("edges")), vec![0, 63, 64, 129]);
+        assert!(registry.tag_bits.read().contains_key(&tag("empty")));
+        assert_eq!(registry.tag_entries(&tag("odd")).len(), 65);
+    }
+
+    #[test]
+    fn registry_keys_are_under_root() {
+        assert_eq!(root_key().to_string(), "minecraft:root");
+        let key = RegistryKey::<Registry<Entry>>::of_reg(Identifier::parse("minecraft:block"));
+        assert_eq!(key.to_canonical_string(), "minecraft:root#minecraft:block");
+    }
+
+    #[test]
+    fn unknown_id_is_not_interned() {
+        let registry = registry(&["registry_test_c"]);
+        let unknown = Identifier::parse("registry_test_unknown");
+
+        assert!(registry.get_from_id(&unknown).is_none());
+        assert!(!registry.contains_id(&unknown));
+        assert!(InternedId::get(&unknown).is_none());
+    }
+
+    fn undefaulted() -> Registry<Entry> {
+        let mut builder = Builder::new();
+        builder
+            .register(Entry(0), Identifier::parse("registry_test_undefaulted"))
+            .unwrap();
+        builder.build_standalone(RegistryKey::of_reg(Identifier::parse("test_entries")), None)
+    }
+
+    #[test]
+    fn try_default_entry() {
+        let undefaulted = undefaulted();
+        assert!(!undefaulted.is_defaulted());
+        assert!(undefaulted.try_default_entry().is_none());
+        assert_eq!(undefaulted.get_from_raw(0).unwrap().raw_id(), 0);
+
+        let defaulted = registry(&["registry_test_d", "registry_test_e"]);
+        assert!(defaulted.is_defaulted());
+        assert_eq!(defaulted.try_default_entry().map(|e| e.0), Some(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn default_entry_panics_if_undefaulted() {
+        undefaulted().default_entry();
+    }
+
+    #[test]
+    fn tags_iterate_sorted() {
+        let registry = registry(&["registry_test_f", "registry_test_g", "registry_test_h"]);
+        let tag = tag::TagKey::new(registry.key.clone(), Identifier::parse("shuffled"));
+
+        let mut tags = hashbrown::HashMap::new();
+        tags.insert(tag.clone(), vec![2, 0, 2, 1, 7]);
+        registry.populate_tags(tags);
+
+        assert_eq!(
+            registry.iter_tag(&tag).map(|e| e.0).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert!(registry.iter_tag(&tag).all(|(raw, e)| e.raw_id() == raw));
+    }
+
+    #[test]
+    fn canonical_key_round_trip() {
+        let reg = RegistryKey::<Registry<Entry>>::of_reg(Identifier::parse("minecraft:item"));
+        let key = RegistryKey::new(&reg, Identifier::parse("mymod:gem"));
+        let string = key.to_canonical_string();
+        assert_eq!(string, "minecraft:item#mymod:gem");
+
+        let (reg_id, value) = parse_registry_key(&string).unwrap();
+        assert_eq!(&reg_id, key.reg());
+        assert_eq!(&value, key.value());
+        assert!(key.is_of(&reg));
+
+        assert!(parse_registry_key("minecraft:item").is_err());
+        assert!(parse_registry_key("minecraft:item#").is_err());
+    }
+
+    #[test]
+    fn extend_builders() {
+        let builder = |ids: &[&str]| {
+            let mut builder = Builder::new();
+            for id in ids {
+                builder.register(Entry(0), Identifier::parse(id)).unwrap();
+            }
+            builder
+        };
+
+        let mut base = builder(&["registry_test_extend_a", "registry_test_extend_b"]);
+        let err = base
+            .extend(builder(&[
+                "registry_test_extend_c",
+                "registry_test_extend_a",
+            ]))
+            .unwrap_err();
+        assert!(err.to_string().contains("minecraft:registry_test_extend_a"));
+
+        base.extend(builder(&["registry_test_extend_c"])).unwrap();
+        let registry =
+            base.build_standalone(RegistryKey::of_reg(Identifier::parse("test_entries")), None);
+        let ids = [
+            "registry_test_extend_a",
+            "registry_test_extend_b",
+            "registry_test_extend_c",
+        ];
+        assert_eq!(registry.iter().count(), ids.len());
+        for (raw, id) in ids.into_iter().enumerate() {
+            assert_eq!(registry.get_from_id(&Identifier::parse(id)).unwrap().0, raw);
+        }
+    }
+
+    #[test]
+    fn standalone_registry_without_freezer() {
+        let mut builder = Builder::new();
+        builder
+            .register(Entry(0), Identifier::parse("registry_test_standalone_a"))
+            .unwrap();
+        builder
+            .register(Entry(0), Identifier::parse("registry_test_standalone_b"))
+            .unwrap();
+
+        let registry = builder.build_standalone(
+            RegistryKey::of_reg(Identifier::parse("test_standalone")),
+            Some(Identifier::parse("registry_test_standalone_b")),
+        );
+        assert_eq!(registry.key.value(), &Identifier::parse("test_standalone"));
+        assert_eq!(registry.default_entry().0, 1);
+
+        let key = RegistryKey::new(
+            &registry.key,
+            Identifier::parse("registry_test_standalone_a"),
+        );
+        let (raw, holder) = registry.get_from_key(&key).unwrap();
+        assert_eq!(raw, 0);
+        assert!(holder.key() == &key);
+        assert_eq!(registry.get_from_raw(1).unwrap().raw_id(), 1);
+        assert!(registry.get_from_raw(2).is_none());
+    }
+
+    #[test]
+    fn iter_indexed_yields_every_entry() {
+        use crate::util::collections::Indexed;
+
+        let ids = [
+            "registry_test_indexed_a",
+            "registry_test_indexed_b",
+            "registry_test_indexed_c",
+        ];
+        let registry = registry(&ids);
+
+        let entries = Indexed::<Entry>::iter_indexed(&registry).collect::<Vec<_>>();
+        assert_eq!(entries, [(0, &Entry(0)), (1, &Entry(1)), (2, &Entry(2))]);
+
+        for (raw, holder) in Indexed::<Holder<Entry>>::iter_indexed(&registry) {
+            assert_eq!(holder.key().value(), &Identifier::parse(ids[raw]));
+        }
+        assert_eq!(
+            Indexed::<Holder<Entry>>::iter_indexed(&registry).count(),
+            ids.len()
+        );
+    }
+
+    #[test]
+    fn build_hooks_run_per_entry() {
+        let collected = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let mut builder = Builder::new();
+        {
+            let collected = collected.clone();
+            builder.on_each(move |raw, id, entry: &Entry| {
+                collected.lock().push((raw, id.clone(), entry.0));
+            });
+        }
+        for id in ["registry_test_hook_a", "registry_test_hook_b"] {
+            builder.register(Entry(0), Identifier::parse(id)).unwrap();
+        }
+        assert!(collected.lock().is_empty());
+
+        builder.build_standalone(RegistryKey::of_reg(Identifier::parse("test_entries")), None);
+        assert_eq!(
+            *collected.lock(),
+            [
+                (0, Identifier::parse("registry_test_hook_a"), 0),
+                (1, Identifier::parse("registry_test_hook_b"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_ingredient_ids_of_each_form() {
+        let mut builder = Builder::new();
+        for id in [
+            "minecraft:oak_planks",
+            "minecraft:birch_planks",
+            "minecraft:stick",
+        ] {
+            builder.register(Entry(0), Identifier::parse(id)).unwrap();
+        }
+        builder.alias(
+            Identifier::parse("minecraft:wood_planks"),
+            Identifier::parse("minecraft:oak_planks"),
+        );
+        let registry =
+            builder.build_standalone(RegistryKey::of_reg(Identifier::parse("test_entries")), None);
+
+        let mut tags = hashbrown::HashMap::new();
+        tags.insert(
+            tag::TagKey::new(registry.key.clone(), Identifier::parse("minecraft:planks")),
+            vec![1, 0],
+        );
+        registry.populate_tags(tags);
+
+        assert_eq!(registry.resolve_ingredient_ids("minecraft:stick"), [2]);
+        assert_eq!(
+            registry.resolve_ingredient_ids("minecraft:wood_planks"),
+            [0]
+        );
+        assert_eq!(registry.resolve_ingredient_ids("#minecraft:planks"), [0, 1]);
+
+        assert!(registry
+            .resolve_ingredient_ids("minecraft:planks")
+            .is_empty());
+        assert!(registry
+            .resolve_ingredient_ids("#minecraft:logs")
+            .is_empty());
+        assert!(registry.resolve_ingredient_ids("#Invalid").is_empty());
+        assert!(registry.resolve_ingredient_ids("").is_empty());
+    }
+
+    #[test]
+    fn tags_getter_reads_bindings() {
+        let registry = registry(&["registry_test_tags_a", "registry_test_tags_b"]);
+        let tag = tag::TagKey::new(registry.key.clone(), Identifier::parse("pair"));
+        assert!(registry.tags().is_empty());
+
+        registry.populate_tags(hashbrown::HashMap::from([(tag.clone(), vec![1, 0])]));
+        assert_eq!(registry.tags().get(&tag), Some(&vec![0, 1]));
+        assert_eq!(registry.tags().len(), 1);
+    }
+
+    #[test]
+    fn diff_reports_changed_values() {
+        #[derive(serde::Serialize)]
+        struct Weighted {
+            #[serde(skip)]
+            id: usize,
+            weight: u32,
+        }
+
+        impl Registration for Weighted {
+            fn accept(&mut self, id: usize) {
+                self.id = id;
+            }
+
+            fn raw_id(&self) -> usize {
+                self.id
+            }
+        }
+
+        let build = |weights: &[(&str, u32)]| {
+            let mut builder = Builder::new();
+            for (id, weight) in weights {
+                builder
+                    .register(
+                        Weighted {
+                            id: 0,
+                            weight: *weight,
+                        },
+                        Identifier::parse(id),
+                    )
+                    .unwrap();
+            }
+            builder
+                .build_standalone(
+                    RegistryKey::of_reg(Identifier::parse("test_weighted")),
+                    None,
+                )
+                .value_fingerprint()
+        };
+
+        let old = build(&[("diff_a", 1), ("diff_b", 2), ("diff_c", 3)]);
+        let new = build(&[("diff_a", 1), ("diff_b", 5), ("diff_c", 3)]);
+
+        assert_eq!(old.len(), 3);
+        assert_eq!(diff_values(&old, &old), []);
+        assert_eq!(diff_values(&old, &new), [Identifier::parse("diff_b")]);
+    }
+
+    #[test]
+    fn concurrent_tag_queries_and_ensures() {
+        let registry = registry(&["registry_test_threads_a", "registry_test_threads_b"]);
+        let tag = |path: &str| tag::TagKey::new(registry.key.clone(), Identifier::parse(path));
+        registry.populate_tags(hashbrown::HashMap::from([(tag("threads_bound"), vec![1])]));
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let (registry, tag) = (&registry, &tag);
+                scope.spawn(move || {
+                    for i in 0..100 {
+                        assert_eq!(registry.tag_entries(&tag("threads_bound")), [1]);
+                        registry.ensure_tag(&tag(&format!("threads_{}", i % 8)));
+                        registry.ensure_tag(&tag("threads_bound"));
+                    }
+                });
+            }
+        });
+
+        assert_eq!(registry.tag_entries(&tag("threads_bound")), [1]);
+        for i in 0..8 {
+            assert!(registry
+                .tags()
+                .get(&tag(&format!("threads_{i}")))
+                .is_some_and(Vec::is_empty));
+        }
+    }
+}
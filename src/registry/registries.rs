@@ -7,3 +7,110 @@ pub fn root_key() -> Identifier {
 pub static ITEM: super::Freezer<crate::item::Item> = super::Freezer::new(super::Builder::new());
 pub static BLOCK: super::Freezer<crate::block::Block> = super::Freezer::new(super::Builder::new());
 pub static FLUID: super::Freezer<crate::fluid::Fluid> = super::Freezer::new(super::Builder::new());
+pub static BIOME: super::Freezer<crate::world::biome::Biome> =
+    super::Freezer::new(super::Builder::new());
+pub static DIMENSION_TYPE: super::Freezer<crate::world::dimension::DimensionType> =
+    super::Freezer::new(super::Builder::new());
+
+/// Register entries used by unit tests and freeze the global registries,
+/// which only happens once since tests share the process.
+#[cfg(test)]
+pub(crate) fn freeze_for_tests() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+
+    fn id(path: &str) -> Identifier {
+        Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap()
+    }
+
+    INIT.call_once(|| {
+        use crate::block::{Block, BlockDescriptor};
+
+        {
+            let mut blocks = BLOCK.mutable.lock();
+            let blocks = blocks.as_mut().unwrap();
+            let transparent = |full_cube| BlockDescriptor {
+                opacity: 0,
+                full_cube,
+            };
+
+            blocks
+                .register(
+                    Block::with_descriptor(transparent(false), Vec::new()).unwrap(),
+                    id("air"),
+                )
+                .unwrap();
+            blocks
+                .register(Block::new(Vec::new()).unwrap(), id("stone"))
+                .unwrap();
+            blocks
+                .register(
+                    Block::with_descriptor(transparent(true), Vec::new()).unwrap(),
+                    id("glass"),
+                )
+                .unwrap();
+            blocks
+                .register(
+                    Block::with_descriptor(transparent(false), Vec::new()).unwrap(),
+                    id("water"),
+                )
+                .unwrap();
+        }
+
+        BLOCK.freeze((super::RegistryKey::of_reg(id("block")), Some(id("air"))));
+        BLOCK.populate_tags(hashbrown::HashMap::from([(
+            super::tag::TagKey::new(BLOCK.key.clone(), id("impermeable")),
+            vec![BLOCK.get_from_id(&id("glass")).unwrap().0],
+        )]));
+
+        {
+            use crate::item::{Item, ItemDescriptor};
+
+            let mut items = ITEM.mutable.lock();
+            let items = items.as_mut().unwrap();
+            for id in [
+                id("air"),
+                id("stone"),
+                id("apple"),
+                Identifier::parse("mymod:gem"),
+                id("bucket"),
+                id("water_bucket"),
+                id("lava_bucket"),
+            ] {
+                items
+                    .register(Item::new(ItemDescriptor::default()), id)
+                    .unwrap();
+            }
+        }
+
+        ITEM.freeze((super::RegistryKey::of_reg(id("item")), Some(id("air"))));
+        ITEM.populate_tags(hashbrown::HashMap::from([(
+            super::tag::TagKey::new(ITEM.key.clone(), Identifier::parse("c:unstackable")),
+            vec![ITEM.get_from_id(&id("lava_bucket")).unwrap().0],
+        )]));
+
+        {
+            let mut fluids = FLUID.mutable.lock();
+            let fluids = fluids.as_mut().unwrap();
+            for path in ["empty", "water", "lava"] {
+                fluids
+                    .register(crate::fluid::Fluid::new(Vec::new()).unwrap(), id(path))
+                    .unwrap();
+            }
+        }
+
+        FLUID.freeze((super::RegistryKey::of_reg(id("fluid")), Some(id("empty"))));
+
+        // the water block holds the default water state
+        let water: &'static crate::fluid::Fluid =
+            FLUID.get_from_id(&id("water")).unwrap().1.value();
+        crate::block::EVENTS.write().register(
+            Some(*BLOCK.get_from_id(&id("water")).unwrap().1.value()),
+            crate::block::VanillaBlockCallback::FluidStateMap(Box::new(move |_| {
+                Some(crate::state::States::get_shared(
+                    crate::util::Ref(water.states()),
+                    0,
+                ))
+            })),
+        );
+    });
+}
@@ -0,0 +1,247 @@
+use crate::prelude::*;
+
+/// A command executor, receiving the parsed context.
+pub type CommandExecutor<S> =
+    Box<dyn Fn(&CommandContext<'_, S>) -> anyhow::Result<i32> + 'static + Send + Sync>;
+
+/// Parser type of an argument node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArgumentType {
+    /// A 32-bit integer.
+    Integer,
+    /// A single word.
+    String,
+    /// An [`Identifier`], with the default namespace if absent.
+    Identifier,
+}
+
+impl ArgumentType {
+    fn parse(self, input: &str) -> anyhow::Result<ArgumentValue> {
+        Ok(match self {
+            ArgumentType::Integer => ArgumentValue::Integer(input.parse()?),
+            ArgumentType::String => ArgumentValue::String(input.to_string()),
+            ArgumentType::Identifier => ArgumentValue::Identifier(Identifier::try_parse(input)?),
+        })
+    }
+}
+
+/// A parsed argument value.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ArgumentValue {
+    Integer(i32),
+    String(String),
+    Identifier(Identifier),
+}
+
+enum NodeKind {
+    Literal(String),
+    Argument(String, ArgumentType),
+}
+
+/// A node of the command tree, built with [`literal`] and [`argument`].
+pub struct CommandNode<S> {
+    kind: NodeKind,
+    children: Vec<CommandNode<S>>,
+    executor: Option<CommandExecutor<S>>,
+}
+
+impl<S> CommandNode<S> {
+    /// Add a child node to this node.
+    pub fn then(mut self, child: Self) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Set the executor called when the input ends at this node.
+    pub fn executes<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&CommandContext<'_, S>) -> anyhow::Result<i32> + 'static + Send + Sync,
+    {
+        self.executor = Some(Box::new(f));
+        self
+    }
+
+    fn parse(&self, input: &str) -> Option<Option<(String, ArgumentValue)>> {
+        match &self.kind {
+            NodeKind::Literal(literal) => (literal == input).then_some(None),
+            NodeKind::Argument(name, ty) => ty.parse(input).ok().map(|e| Some((name.clone(), e))),
+        }
+    }
+}
+
+/// Creates a literal node matching the exact word.
+pub fn literal<S>(name: &str) -> CommandNode<S> {
+    CommandNode {
+        kind: NodeKind::Literal(name.to_string()),
+        children: Vec::new(),
+        executor: None,
+    }
+}
+
+/// Creates an argument node parsing a word with the given type.
+pub fn argument<S>(name: &str, ty: ArgumentType) -> CommandNode<S> {
+    CommandNode {
+        kind: NodeKind::Argument(name.to_string(), ty),
+        children: Vec::new(),
+        executor: None,
+    }
+}
+
+/// Context of an executing command.
+pub struct CommandContext<'a, S> {
+    /// Source of the command.
+    pub source: &'a S,
+    arguments: hashbrown::HashMap<String, ArgumentValue>,
+}
+
+impl<S> CommandContext<'_, S> {
+    /// Get a parsed argument by its name.
+    pub fn argument(&self, name: &str) -> Option<&ArgumentValue> {
+        self.arguments.get(name)
+    }
+
+    pub fn get_integer(&self, name: &str) -> Option<i32> {
+        match self.arguments.get(name)? {
+            ArgumentValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        match self.arguments.get(name)? {
+            ArgumentValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_identifier(&self, name: &str) -> Option<&Identifier> {
+        match self.arguments.get(name)? {
+            ArgumentValue::Identifier(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A brigadier-like command dispatcher, routing whitespace-separated
+/// input through a tree of literal and argument nodes.
+pub struct CommandDispatcher<S> {
+    roots: Vec<CommandNode<S>>,
+}
+
+impl<S> CommandDispatcher<S> {
+    pub fn new() -> Self {
+        Self { roots: Vec::new() }
+    }
+
+    /// Register a root node into this dispatcher.
+    pub fn register(&mut self, node: CommandNode<S>) {
+        self.roots.push(node);
+    }
+
+    /// Parse and execute the input, returning the result of the executor.
+    ///
+    /// Literal nodes are preferred over argument nodes, and
+    /// nodes are tried in their registration order.
+    pub fn execute(&self, input: &str, source: &S) -> anyhow::Result<i32> {
+        let input = input.strip_prefix('/').unwrap_or(input);
+        let mut arguments = hashbrown::HashMap::new();
+        let mut nodes = &self.roots;
+        let mut current: Option<&CommandNode<S>> = None;
+
+        for word in input.split_whitespace() {
+            let (node, arg) = nodes
+                .iter()
+                .filter(|e| matches!(e.kind, NodeKind::Literal(_)))
+                .chain(
+                    nodes
+                        .iter()
+                        .filter(|e| matches!(e.kind, NodeKind::Argument(..))),
+                )
+                .find_map(|e| e.parse(word).map(|arg| (e, arg)))
+                .ok_or_else(|| anyhow::anyhow!("Unknown or incorrect argument: {word}"))?;
+
+            if let Some((name, value)) = arg {
+                arguments.insert(name, value);
+            }
+            nodes = &node.children;
+            current = Some(node);
+        }
+
+        let executor = current
+            .ok_or_else(|| anyhow::anyhow!("Empty command"))?
+            .executor
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Incomplete command: {input}"))?;
+
+        executor(&CommandContext { source, arguments })
+    }
+}
+
+impl<S> Default for CommandDispatcher<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Source = std::sync::Mutex<Vec<String>>;
+
+    #[test]
+    fn execute_give_command() {
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.register(
+            literal("give").then(
+                argument::<Source>("item", ArgumentType::Identifier)
+                    .executes(|ctx| {
+                        let item = ctx.get_identifier("item").unwrap();
+                        ctx.source.lock().unwrap().push(format!("1 {item}"));
+                        Ok(1)
+                    })
+                    .then(
+                        argument::<Source>("count", ArgumentType::Integer).executes(|ctx| {
+                            let item = ctx.get_identifier("item").unwrap();
+                            let count = ctx.get_integer("count").unwrap();
+                            ctx.source.lock().unwrap().push(format!("{count} {item}"));
+                            Ok(count)
+                        }),
+                    ),
+            ),
+        );
+        dispatcher.register(
+            literal("say").then(
+                argument::<Source>("message", ArgumentType::String)
+                    .executes(|ctx| Ok(ctx.get_string("message").unwrap().len() as i32)),
+            ),
+        );
+
+        let source = Source::new(Vec::new());
+        assert_eq!(
+            dispatcher
+                .execute("/give minecraft:apple", &source)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            dispatcher
+                .execute("give minecraft:stone  16", &source)
+                .unwrap(),
+            16
+        );
+        assert_eq!(
+            *source.lock().unwrap(),
+            ["1 minecraft:apple", "16 minecraft:stone"]
+        );
+        assert_eq!(dispatcher.execute("say hello", &source).unwrap(), 5);
+
+        assert!(dispatcher.execute("", &source).is_err());
+        assert!(dispatcher.execute("give", &source).is_err());
+        assert!(dispatcher
+            .execute("give minecraft:stone many", &source)
+            .is_err());
+        assert!(dispatcher.execute("take minecraft:stone", &source).is_err());
+        assert_eq!(source.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,147 @@
+use futures::{SinkExt, StreamExt};
+
+use crate::network::{Decode, Encode};
+
+/// State of a connection, deciding how packets are interpreted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConnectionState {
+    Handshaking,
+    Status,
+    Login,
+    Play,
+}
+
+/// A client connection exchanging length-prefixed packet frames
+/// through a [`crate::network::MinecraftCodec`].
+pub struct Connection {
+    framed: tokio_util::codec::Framed<tokio::net::TcpStream, crate::network::MinecraftCodec>,
+    pub state: ConnectionState,
+}
+
+impl Connection {
+    /// Max length of a packet frame.
+    pub const MAX_FRAME_LEN: usize = crate::network::MinecraftCodec::DEFAULT_MAX_FRAME_LEN;
+
+    pub fn new(stream: tokio::net::TcpStream) -> Self {
+        Self {
+            framed: tokio_util::codec::Framed::new(
+                stream,
+                crate::network::MinecraftCodec::with_max_frame_len(Self::MAX_FRAME_LEN),
+            ),
+            state: ConnectionState::Handshaking,
+        }
+    }
+
+    /// Read a packet frame and return its content including the packet id.
+    pub async fn read_frame(&mut self) -> anyhow::Result<bytes::BytesMut> {
+        self.framed
+            .next()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Connection closed"))?
+    }
+
+    /// Write the packet content as a frame, prefixed with its length.
+    pub async fn write_frame(&mut self, content: bytes::Bytes) -> anyhow::Result<()> {
+        self.framed.send(content).await
+    }
+
+    /// Drive this connection through the handshake and the status
+    /// exchange (server list ping).
+    ///
+    /// Connections are closed when they ask for login,
+    /// which is not supported yet.
+    pub async fn handle(mut self) -> anyhow::Result<()> {
+        loop {
+            let mut frame = self.read_frame().await?;
+            let id = crate::util::VarInt::decode(&mut frame)?;
+
+            match (self.state, id) {
+                (ConnectionState::Handshaking, 0x00) => {
+                    let _protocol = crate::util::VarInt::decode(&mut frame)?;
+                    let _address = String::decode(&mut frame)?;
+                    let _port = u16::decode(&mut frame)?;
+
+                    self.state = match crate::util::VarInt::decode(&mut frame)? {
+                        1 => ConnectionState::Status,
+                        2 => ConnectionState::Login,
+                        next => return Err(anyhow::anyhow!("Invalid next state {next}")),
+                    };
+                }
+                (ConnectionState::Status, 0x00) => {
+                    let mut packet = bytes::BytesMut::new();
+                    crate::util::VarInt(0x00).encode(&mut packet)?;
+                    let status = super::STATUS.read().to_json();
+                    status.encode(&mut packet)?;
+                    self.write_frame(packet.freeze()).await?;
+                }
+                (ConnectionState::Status, 0x01) => {
+                    let payload = i64::decode(&mut frame)?;
+
+                    let mut packet = bytes::BytesMut::new();
+                    crate::util::VarInt(0x01).encode(&mut packet)?;
+                    payload.encode(&mut packet)?;
+                    self.write_frame(packet.freeze()).await?;
+                    return Ok(());
+                }
+                (ConnectionState::Login, _) | (ConnectionState::Play, _) => return Ok(()),
+                (state, id) => {
+                    return Err(anyhow::anyhow!(
+                        "Unexpected packet {id:#04x} in state {state:?}"
+                    ))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+
+    use super::*;
+    use crate::network::MinecraftCodec;
+
+    #[tokio::test]
+    async fn status_handshake() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            Connection::new(stream).handle().await
+        });
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut client = tokio_util::codec::Framed::new(stream, MinecraftCodec::new());
+
+        let mut handshake = bytes::BytesMut::new();
+        crate::util::VarInt(0x00).encode(&mut handshake).unwrap();
+        crate::util::VarInt(763).encode(&mut handshake).unwrap();
+        "localhost".encode(&mut handshake).unwrap();
+        addr.port().encode(&mut handshake).unwrap();
+        crate::util::VarInt(1).encode(&mut handshake).unwrap();
+        client.send(handshake.freeze()).await.unwrap();
+
+        let mut request = bytes::BytesMut::new();
+        crate::util::VarInt(0x00).encode(&mut request).unwrap();
+        client.send(request.freeze()).await.unwrap();
+
+        let mut response = client.next().await.unwrap().unwrap();
+        assert_eq!(crate::util::VarInt::decode(&mut response).unwrap(), 0x00);
+        let status =
+            crate::server::StatusResponse::from_json(&String::decode(&mut response).unwrap())
+                .unwrap();
+        assert_eq!(status, *crate::server::STATUS.read());
+
+        let mut ping = bytes::BytesMut::new();
+        crate::util::VarInt(0x01).encode(&mut ping).unwrap();
+        0x1234_5678_i64.encode(&mut ping).unwrap();
+        client.send(ping.freeze()).await.unwrap();
+
+        let mut pong = client.next().await.unwrap().unwrap();
+        assert_eq!(crate::util::VarInt::decode(&mut pong).unwrap(), 0x01);
+        assert_eq!(i64::decode(&mut pong).unwrap(), 0x1234_5678);
+
+        server.await.unwrap().unwrap();
+        assert!(client.next().await.is_none());
+    }
+}
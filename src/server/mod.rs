@@ -1 +1,39 @@
-pub async fn run() {}
+mod command;
+mod connection;
+mod status;
+
+pub use command::*;
+pub use connection::*;
+pub use status::*;
+
+/// The dedicated server.
+pub struct Server;
+
+impl Server {
+    /// The default port of servers.
+    pub const DEFAULT_PORT: u16 = 25565;
+
+    /// Bind to the address and accept connections, spawning
+    /// a task to handle each connection.
+    pub async fn listen(addr: std::net::SocketAddr) -> std::io::Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!("Listening on {addr}");
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+
+            tokio::spawn(async move {
+                if let Err(err) = Connection::new(stream).handle().await {
+                    tracing::debug!("Connection from {peer} closed with error: {err}");
+                }
+            });
+        }
+    }
+}
+
+pub async fn run() {
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], Server::DEFAULT_PORT));
+    if let Err(err) = Server::listen(addr).await {
+        tracing::error!("Failed to run server: {err}");
+    }
+}
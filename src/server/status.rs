@@ -0,0 +1,142 @@
+/// Status of the server answered to server list pings.
+pub static STATUS: once_cell::sync::Lazy<parking_lot::RwLock<StatusResponse>> =
+    once_cell::sync::Lazy::new(|| parking_lot::RwLock::new(StatusResponse::default()));
+
+/// The server status shown in the server list of clients.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StatusResponse {
+    pub version_name: String,
+    pub protocol: i32,
+    pub max_players: u32,
+    pub online_players: u32,
+    /// Plain text description, also known as the motd.
+    pub description: String,
+    /// The server icon as a base64 png data url.
+    pub favicon: Option<String>,
+}
+
+impl StatusResponse {
+    /// Serialize into the vanilla status json.
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::json!({
+            "version": { "name": self.version_name, "protocol": self.protocol },
+            "players": { "max": self.max_players, "online": self.online_players },
+            "description": { "text": self.description },
+        });
+
+        if let Some(favicon) = &self.favicon {
+            json["favicon"] = favicon.as_str().into();
+        }
+
+        json.to_string()
+    }
+
+    /// Parse from the vanilla status json, where the description
+    /// can be either a string or a text component with `text`.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let field = |pointer: &str| {
+            value
+                .pointer(pointer)
+                .ok_or_else(|| anyhow::anyhow!("Missing status field {pointer}"))
+        };
+        let number = |pointer: &str| {
+            field(pointer)?
+                .as_i64()
+                .ok_or_else(|| anyhow::anyhow!("Status field {pointer} is not a number"))
+        };
+
+        Ok(Self {
+            version_name: field("/version/name")?
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            protocol: i32::try_from(number("/version/protocol")?)?,
+            max_players: u32::try_from(number("/players/max")?)?,
+            online_players: u32::try_from(number("/players/online")?)?,
+            description: match value.get("description") {
+                Some(serde_json::Value::String(text)) => text.clone(),
+                Some(description) => description
+                    .get("text")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                None => String::new(),
+            },
+            favicon: value
+                .get("favicon")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string),
+        })
+    }
+}
+
+impl Default for StatusResponse {
+    fn default() -> Self {
+        Self {
+            version_name: "1.20.1".to_string(),
+            protocol: 763,
+            max_players: 20,
+            online_players: 0,
+            description: "A Rimecraft Server".to_string(),
+            favicon: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_json_round_trip() {
+        let status = StatusResponse {
+            online_players: 3,
+            description: "Hello \"world\"".to_string(),
+            favicon: Some("data:image/png;base64,AAAA".to_string()),
+            ..Default::default()
+        };
+
+        let json = status.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"]["protocol"], 763);
+        assert_eq!(value["players"]["online"], 3);
+        assert_eq!(value["description"]["text"], "Hello \"world\"");
+        assert_eq!(StatusResponse::from_json(&json).unwrap(), status);
+
+        let status = StatusResponse::default();
+        assert!(!status.to_json().contains("favicon"));
+        assert_eq!(
+            StatusResponse::from_json(&status.to_json()).unwrap(),
+            status
+        );
+    }
+
+    #[test]
+    fn status_json_description_forms() {
+        let json = r#"{"version":{"name":"1.20.1","protocol":763},
+            "players":{"max":20,"online":0},"description":"plain"}"#;
+        assert_eq!(
+            StatusResponse::from_json(json).unwrap().description,
+            "plain"
+        );
+
+        assert!(StatusResponse::from_json(r#"{"version":{"name":"x"}}"#).is_err());
+        assert!(StatusResponse::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn status_json_rejects_out_of_range_numbers() {
+        let json = |protocol: i64, max: i64, online: i64| {
+            format!(
+                r#"{{"version":{{"name":"x","protocol":{protocol}}},
+                "players":{{"max":{max},"online":{online}}},"description":""}}"#
+            )
+        };
+        assert!(StatusResponse::from_json(&json(763, 20, 0)).is_ok());
+        assert!(StatusResponse::from_json(&json(763, -1, 0)).is_err());
+        assert!(StatusResponse::from_json(&json(763, 20, -3)).is_err());
+        assert!(StatusResponse::from_json(&json(763, 1 << 32, 0)).is_err());
+        assert!(StatusResponse::from_json(&json(1 << 31, 20, 0)).is_err());
+    }
+}
@@ -206,13 +206,18 @@ impl<T: Deref<Target = State>> Hash for Shared<T> {
     }
 }
 
+/// Builds every combination of property values as a state.
+///
+/// The product starts from a single empty combination, so a holder
+/// without any properties still gets exactly one state to use as its
+/// default; starting from no combinations at all would leave it empty.
 fn new_states<E: Clone, T: Deref<Target = State> + From<(E, State)>>(
     data: E,
     def_state: hashbrown::HashMap<property::Property, u8>,
     properties: hashbrown::HashMap<String, property::Property>,
 ) -> States<T> {
     let mut states_raw: Vec<State> = Vec::new();
-    let mut temp: Vec<Vec<(property::Property, u8)>> = Vec::new();
+    let mut temp: Vec<Vec<(property::Property, u8)>> = vec![Vec::new()];
 
     for property in properties.values() {
         temp = temp
@@ -307,3 +312,33 @@ impl StatesBuilder {
         new_states(data, def_state, self.properties)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Plain(State);
+
+    impl From<((), State)> for Plain {
+        fn from(value: ((), State)) -> Self {
+            Self(value.1)
+        }
+    }
+
+    impl Deref for Plain {
+        type Target = State;
+
+        fn deref(&self) -> &Self::Target {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn no_properties_yields_one_state() {
+        let states: States<Plain> = StatesBuilder::new().build((), hashbrown::HashMap::new());
+        assert_eq!(states.states().len(), 1);
+        assert!(states.properties().is_empty());
+        assert!(states.default_state().entries().is_empty());
+        assert!(states.from_id(0).is_some());
+    }
+}
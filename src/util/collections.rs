@@ -5,6 +5,49 @@ pub trait Indexed<T> {
     fn get_raw_id(&self, value: &T) -> Option<usize>;
     fn get(&self, index: usize) -> Option<&T>;
     fn len(&self) -> usize;
+
+    /// Iterate over all present values with raw ids in `0..len()`,
+    /// along with their raw ids.
+    fn iter_indexed(&self) -> IndexedIter<'_, T, Self>
+    where
+        Self: Sized,
+    {
+        IndexedIter {
+            indexed: self,
+            index: 0,
+            len: self.len(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Iterator over an [`Indexed`], see [`Indexed::iter_indexed`].
+pub struct IndexedIter<'a, T, I: Indexed<T>> {
+    indexed: &'a I,
+    index: usize,
+    len: usize,
+    _marker: std::marker::PhantomData<&'a T>,
+}
+
+impl<'a, T: 'a, I: Indexed<T>> Iterator for IndexedIter<'a, T, I> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < self.len {
+            let index = self.index;
+            self.index += 1;
+
+            if let Some(value) = self.indexed.get(index) {
+                return Some((index, value));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.index))
+    }
 }
 
 /// An id list, just targeting the `IdList` in MCJE.
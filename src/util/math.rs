@@ -219,7 +219,7 @@ impl std::fmt::Display for Box {
 /// Represents the position of a block in a three-dimensional volume.
 ///
 /// The position is integer-valued.
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
 pub struct BlockPos(glam::IVec3);
 
 impl BlockPos {
@@ -241,6 +241,27 @@ impl BlockPos {
     pub fn new(x: i32, y: i32, z: i32) -> Self {
         Self(glam::IVec3 { x, y, z })
     }
+
+    /// The position of the chunk containing this position.
+    pub fn chunk_pos(self) -> ChunkPos {
+        ChunkPos::from_block(self)
+    }
+
+    /// The adjacent position towards the given direction.
+    pub fn offset(self, direction: Direction) -> Self {
+        Self(self.0 + direction.vector())
+    }
+
+    /// The block position containing the given world coordinates.
+    /// Each axis is floored, so `-0.5` maps to `-1` rather than `0`.
+    pub fn from_vec3(value: glam::DVec3) -> Self {
+        Self(value.floor().as_ivec3())
+    }
+
+    /// The world coordinates of the center of this block.
+    pub fn center_vec3(self) -> glam::DVec3 {
+        self.0.as_dvec3() + 0.5
+    }
 }
 
 impl Deref for BlockPos {
@@ -284,6 +305,56 @@ impl From<i64> for BlockPos {
     }
 }
 
+/// A box with integer coords, where both minimum
+/// and maximum coords are inclusive.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BlockBox {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+    pub max_z: i32,
+}
+
+impl BlockBox {
+    /// Creates a box of the given positions as corners.
+    pub fn new(pos1: BlockPos, pos2: BlockPos) -> Self {
+        let min = pos1.0.min(pos2.0);
+        let max = pos1.0.max(pos2.0);
+
+        Self {
+            min_x: min.x,
+            min_y: min.y,
+            min_z: min.z,
+            max_x: max.x,
+            max_y: max.y,
+            max_z: max.z,
+        }
+    }
+
+    /// Whether the given position is inside this box.
+    pub fn contains(&self, pos: BlockPos) -> bool {
+        (self.min_x..=self.max_x).contains(&pos.x)
+            && (self.min_y..=self.max_y).contains(&pos.y)
+            && (self.min_z..=self.max_z).contains(&pos.z)
+    }
+
+    /// Positions of chunks this box intersects, ignoring heights.
+    pub fn chunk_positions(&self) -> impl Iterator<Item = ChunkPos> {
+        let (min_x, max_x) = (
+            ChunkSectionPos::section_coord(self.min_x),
+            ChunkSectionPos::section_coord(self.max_x),
+        );
+        let (min_z, max_z) = (
+            ChunkSectionPos::section_coord(self.min_z),
+            ChunkSectionPos::section_coord(self.max_z),
+        );
+
+        (min_x..=max_x).flat_map(move |x| (min_z..=max_z).map(move |z| ChunkPos::new(x, z)))
+    }
+}
+
 /// An immutable pair of two integers representing
 /// the X and Z coords of a chunk.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -296,6 +367,52 @@ impl ChunkPos {
     pub fn new(x: i32, z: i32) -> Self {
         Self { x, z }
     }
+
+    /// The chunk position containing the given block position.
+    pub fn from_block(pos: BlockPos) -> Self {
+        Self {
+            x: ChunkSectionPos::section_coord(pos.x),
+            z: ChunkSectionPos::section_coord(pos.z),
+        }
+    }
+
+    pub fn x(self) -> i32 {
+        self.x
+    }
+
+    pub fn z(self) -> i32 {
+        self.z
+    }
+
+    /// The block position with minimum X and Z coords of this chunk
+    /// at the given Y level.
+    pub fn to_block_min(self, y: i32) -> BlockPos {
+        BlockPos::new(self.x << 4, y, self.z << 4)
+    }
+
+    /// The block position with maximum X and Z coords of this chunk
+    /// at the given Y level.
+    pub fn to_block_max(self, y: i32) -> BlockPos {
+        BlockPos::new((self.x << 4) + 15, y, (self.z << 4) + 15)
+    }
+
+    /// Pack this position into a long.
+    pub fn as_long(self) -> i64 {
+        self.into()
+    }
+
+    /// Unpack a position from a long.
+    pub fn from_long(value: i64) -> Self {
+        value.into()
+    }
+
+    /// Iterate over chunk positions in the square with
+    /// the given `radius` around `center`, row by row.
+    pub fn iterate_around(center: Self, radius: u32) -> impl Iterator<Item = Self> {
+        let r = radius as i32;
+        (center.z - r..=center.z + r)
+            .flat_map(move |z| (center.x - r..=center.x + r).map(move |x| Self { x, z }))
+    }
 }
 
 impl From<i64> for ChunkPos {
@@ -370,6 +487,18 @@ impl Direction {
         }
     }
 
+    /// The unit vector pointing towards this direction.
+    pub fn vector(self) -> glam::IVec3 {
+        match self {
+            Direction::Down => glam::IVec3::NEG_Y,
+            Direction::Up => glam::IVec3::Y,
+            Direction::North => glam::IVec3::NEG_Z,
+            Direction::South => glam::IVec3::Z,
+            Direction::West => glam::IVec3::NEG_X,
+            Direction::East => glam::IVec3::X,
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             Direction::Down => "down",
@@ -477,3 +606,67 @@ pub(crate) mod impl_helper {
         i + 1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_pos_of_negative_blocks() {
+        // `>> 4` floors, unlike `/ 16` which truncates towards zero
+        let pos = BlockPos::new(-1, 0, -17).chunk_pos();
+        assert_eq!((pos.x(), pos.z()), (-1, -2));
+        let pos = ChunkPos::from_block(BlockPos::new(-16, 5, 15));
+        assert_eq!((pos.x(), pos.z()), (-1, 0));
+        let pos = ChunkPos::from_block(BlockPos::new(31, 0, 32));
+        assert_eq!((pos.x(), pos.z()), (1, 2));
+    }
+
+    #[test]
+    fn chunk_pos_block_bounds() {
+        let pos = ChunkPos::new(-2, 3);
+        assert_eq!(pos.to_block_min(7), BlockPos::new(-32, 7, 48));
+        assert_eq!(pos.to_block_max(7), BlockPos::new(-17, 7, 63));
+        assert!(pos.to_block_min(0).chunk_pos() == pos);
+        assert!(pos.to_block_max(0).chunk_pos() == pos);
+    }
+
+    #[test]
+    fn chunk_pos_long_round_trip() {
+        for (x, z) in [(0, 0), (-1, 1), (i32::MIN, i32::MAX), (123, -456)] {
+            let pos = ChunkPos::new(x, z);
+            let unpacked = ChunkPos::from_long(pos.as_long());
+            assert_eq!((unpacked.x(), unpacked.z()), (x, z));
+        }
+        assert_eq!(ChunkPos::new(1, 2).as_long(), 1 | 2 << 32);
+        assert_eq!(ChunkPos::new(-1, 0).as_long(), 0xFFFFFFFF);
+    }
+
+    #[test]
+    fn chunk_pos_iterate_around() {
+        let positions = ChunkPos::iterate_around(ChunkPos::new(-1, 1), 1)
+            .map(|e| (e.x(), e.z()))
+            .collect::<Vec<_>>();
+        assert_eq!(positions.len(), 9);
+        assert_eq!(positions[0], (-2, 0));
+        assert_eq!(positions[1], (-1, 0));
+        assert_eq!(positions[8], (0, 2));
+        assert_eq!(ChunkPos::iterate_around(ChunkPos::new(0, 0), 0).count(), 1);
+    }
+
+    #[test]
+    fn block_pos_from_vec3_floors() {
+        assert_eq!(
+            BlockPos::from_vec3(glam::DVec3::new(-0.5, 0.5, -1.0)),
+            BlockPos::new(-1, 0, -1)
+        );
+        assert_eq!(
+            BlockPos::from_vec3(glam::DVec3::new(-16.01, 63.99, 15.0)),
+            BlockPos::new(-17, 63, 15)
+        );
+
+        let pos = BlockPos::new(-1, 0, 2);
+        assert_eq!(pos.center_vec3(), glam::DVec3::new(-0.5, 0.5, 2.5));
+        assert_eq!(BlockPos::from_vec3(pos.center_vec3()), pos);
+    }
+}
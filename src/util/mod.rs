@@ -2,23 +2,41 @@ use std::{hash::Hash, ops::Deref};
 
 pub mod collections;
 pub mod math;
+pub mod random;
 
-#[derive(PartialEq, Eq, Clone, Hash)]
+/// The namespace of vanilla contents.
+pub const VANILLA_NAMESPACE: &str = "minecraft";
+
+/// A namespaced id, like `minecraft:stone`.
+///
+/// Both parts are reference counted, so cloning is cheap.
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub struct Identifier {
-    namespace: String,
-    path: String,
+    namespace: std::sync::Arc<str>,
+    path: std::sync::Arc<str>,
 }
 
 impl Identifier {
     pub fn new(namespace: &str, path: &str) -> anyhow::Result<Self> {
-        if Self::is_namespace_valid(namespace) && Self::is_path_valid(path) {
+        Self::new_with_policy(namespace, path, &CharPolicy::VANILLA)
+    }
+
+    /// Creates an identifier with characters validated
+    /// by the given [`CharPolicy`].
+    pub fn new_with_policy(
+        namespace: &str,
+        path: &str,
+        policy: &CharPolicy,
+    ) -> anyhow::Result<Self> {
+        if policy.is_namespace_valid(namespace) && policy.is_path_valid(path) {
             Ok(Self {
-                namespace: namespace.to_string(),
-                path: path.to_string(),
+                namespace: namespace.into(),
+                path: path.into(),
             })
         } else {
             Err(anyhow::anyhow!(
-                "Non [a-z0-9/._-] character in identifier: {namespace}:{path}"
+                "Non {} character in identifier: {namespace}:{path}",
+                policy.description
             ))
         }
     }
@@ -31,37 +49,26 @@ impl Identifier {
         Self::split_on(id, ':')
     }
 
+    /// Parse an identifier by splitting on the first `delimiter`,
+    /// or with the [`VANILLA_NAMESPACE`] if the delimiter is absent.
+    ///
+    /// Empty namespaces or paths are rejected, and the rest after the first
+    /// delimiter (like `b:c` in `a:b:c`) is validated as the path.
     pub fn split_on(id: &str, delimiter: char) -> anyhow::Result<Self> {
-        match id.split_once(delimiter) {
-            Some(arr) => Self::new(arr.0, arr.1),
-            None => Self::new("rimecraft", id),
+        let (namespace, path) = id.split_once(delimiter).unwrap_or((VANILLA_NAMESPACE, id));
+
+        if namespace.is_empty() {
+            Err(anyhow::anyhow!("Empty namespace in identifier: {id}"))
+        } else if path.is_empty() {
+            Err(anyhow::anyhow!("Empty path in identifier: {id}"))
+        } else {
+            Self::new(namespace, path)
         }
     }
 
-    fn is_namespace_valid(namespace: &str) -> bool {
-        for c in namespace.chars() {
-            if !(c == '_' || c == '-' || c >= 'a' || c <= 'z' || c >= '0' || c <= '9' || c == '.') {
-                return false;
-            }
-        }
-        true
-    }
-
-    fn is_path_valid(path: &str) -> bool {
-        for c in path.chars() {
-            if !(c == '_'
-                || c == '-'
-                || c >= 'a'
-                || c <= 'z'
-                || c >= '0'
-                || c <= '9'
-                || c == '.'
-                || c == '/')
-            {
-                return false;
-            }
-        }
-        true
+    /// Intern this identifier into the global interner.
+    pub fn intern(self) -> InternedId {
+        InternedId::new(self)
     }
 
     pub fn namespace(&self) -> &str {
@@ -71,6 +78,16 @@ impl Identifier {
     pub fn path(&self) -> &str {
         &self.path
     }
+
+    /// Whether this identifier is in the given namespace.
+    pub fn is_namespace(&self, namespace: &str) -> bool {
+        &*self.namespace == namespace
+    }
+
+    /// Whether this identifier is in the [`VANILLA_NAMESPACE`].
+    pub fn is_vanilla(&self) -> bool {
+        self.is_namespace(VANILLA_NAMESPACE)
+    }
 }
 
 impl std::fmt::Display for Identifier {
@@ -108,6 +125,123 @@ impl<'de> serde::Deserialize<'de> for Identifier {
     }
 }
 
+/// Symbols of leaked identifiers, and identifiers indexed by their symbols.
+#[derive(Default)]
+struct Interner {
+    symbols: hashbrown::HashMap<&'static Identifier, u32>,
+    ids: Vec<&'static Identifier>,
+}
+
+/// Global identifier interner.
+static INTERNER: once_cell::sync::Lazy<parking_lot::RwLock<Interner>> =
+    once_cell::sync::Lazy::new(|| parking_lot::RwLock::new(Default::default()));
+
+/// An [`Identifier`] interned into a global interner as a `u32` symbol,
+/// which is cheap to copy, compare and hash.
+///
+/// Interned identifiers are never freed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct InternedId(u32);
+
+impl InternedId {
+    /// Intern the identifier, or get its symbol if it's already interned.
+    pub fn new(id: Identifier) -> Self {
+        if let Some(e) = Self::get(&id) {
+            return e;
+        }
+
+        let mut interner = INTERNER.write();
+        if let Some(e) = interner.symbols.get(&id) {
+            return Self(*e);
+        }
+
+        let symbol = interner.ids.len() as u32;
+        let leaked: &'static Identifier = Box::leak(Box::new(id));
+        interner.symbols.insert(leaked, symbol);
+        interner.ids.push(leaked);
+        Self(symbol)
+    }
+
+    /// Get symbol of the identifier if it's interned,
+    /// without interning it.
+    pub fn get(id: &Identifier) -> Option<Self> {
+        INTERNER.read().symbols.get(id).copied().map(Self)
+    }
+
+    /// Resolve the identifier of this symbol.
+    pub fn resolve(self) -> &'static Identifier {
+        INTERNER.read().ids[self.0 as usize]
+    }
+
+    /// The raw symbol of this id.
+    pub fn symbol(self) -> u32 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for InternedId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.resolve().fmt(f)
+    }
+}
+
+/// Describes which characters are allowed in
+/// namespaces and paths of [`Identifier`]s.
+#[derive(Clone, Copy)]
+pub struct CharPolicy {
+    namespace: fn(char) -> bool,
+    path: fn(char) -> bool,
+    description: &'static str,
+}
+
+impl CharPolicy {
+    /// The strict vanilla policy, allowing `[a-z0-9_.-]` in
+    /// namespaces and `[a-z0-9/_.-]` in paths.
+    pub const VANILLA: Self = Self {
+        namespace: |c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.'),
+        path: |c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.' | '/'),
+        description: "[a-z0-9/._-]",
+    };
+
+    /// Creates a policy with predicates for namespace and path characters,
+    /// and a description of allowed characters for error messages.
+    pub const fn new(
+        namespace: fn(char) -> bool,
+        path: fn(char) -> bool,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            namespace,
+            path,
+            description,
+        }
+    }
+
+    /// A lenient policy which additionally allows
+    /// uppercase letters and `+`.
+    pub const fn lenient() -> Self {
+        Self {
+            namespace: |c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' | '+'),
+            path: |c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' | '+' | '/'),
+            description: "[a-zA-Z0-9/._+-]",
+        }
+    }
+
+    pub fn is_namespace_valid(&self, namespace: &str) -> bool {
+        namespace.chars().all(self.namespace)
+    }
+
+    pub fn is_path_valid(&self, path: &str) -> bool {
+        path.chars().all(self.path)
+    }
+}
+
+impl Default for CharPolicy {
+    fn default() -> Self {
+        Self::VANILLA
+    }
+}
+
 /// Describes a var int.
 pub struct VarInt(pub i32);
 
@@ -241,3 +375,65 @@ impl<T> Freeze<T> for T {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lenient_policy_accepts_uppercase() {
+        let policy = CharPolicy::lenient();
+        let id = Identifier::new_with_policy("MyMod", "Items/Gem+1", &policy).unwrap();
+        assert_eq!(id.to_string(), "MyMod:Items/Gem+1");
+
+        assert!(Identifier::new_with_policy("mymod", "gem stone", &policy).is_err());
+    }
+
+    #[test]
+    fn vanilla_policy_rejects_uppercase() {
+        assert!(Identifier::new("MyMod", "gem").is_err());
+        assert!(Identifier::new("mymod", "Gem").is_err());
+        assert!(Identifier::new("mymod", "gem+1").is_err());
+        assert!(Identifier::new_with_policy("mymod", "Gem", &CharPolicy::default()).is_err());
+
+        assert!(Identifier::new("my_mod.x-y", "items/gem_1.a-b").is_ok());
+        assert!(Identifier::new("my/mod", "gem").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_empty_components() {
+        let err = Identifier::try_parse("foo:").unwrap_err();
+        assert!(err.to_string().contains("Empty path"));
+        let err = Identifier::try_parse(":bar").unwrap_err();
+        assert!(err.to_string().contains("Empty namespace"));
+        assert!(Identifier::try_parse("").is_err());
+        assert!(Identifier::try_parse(":").is_err());
+
+        // the first colon is the delimiter, and `b:c` is an invalid path
+        assert!(Identifier::try_parse("a:b:c").is_err());
+        assert_eq!(
+            Identifier::split_on("a.b.c", '.').unwrap(),
+            Identifier::new("a", "b.c").unwrap()
+        );
+
+        assert_eq!(
+            Identifier::try_parse("bar").unwrap(),
+            Identifier::new(VANILLA_NAMESPACE, "bar").unwrap()
+        );
+    }
+
+    #[test]
+    fn clones_share_parts() {
+        let id = Identifier::new("mymod", "gem").unwrap();
+        let clone = id.clone();
+        assert!(std::sync::Arc::ptr_eq(&id.namespace, &clone.namespace));
+        assert!(std::sync::Arc::ptr_eq(&id.path, &clone.path));
+
+        let state = std::collections::hash_map::RandomState::new();
+        let hash = |id: &Identifier| std::hash::BuildHasher::hash_one(&state, id);
+        let parsed = Identifier::parse("mymod:gem");
+        assert_eq!(id, parsed);
+        assert_eq!(hash(&id), hash(&parsed));
+        assert_ne!(id, Identifier::parse("mymod:gems"));
+    }
+}
@@ -0,0 +1,116 @@
+/// A linear congruential random number generator
+/// producing the same sequences as `java.util.Random`.
+#[derive(Clone, Debug)]
+pub struct JavaRandom {
+    seed: i64,
+}
+
+impl JavaRandom {
+    const MULTIPLIER: i64 = 0x5DEECE66D;
+    const INCREMENT: i64 = 0xB;
+    const MASK: i64 = (1 << 48) - 1;
+
+    pub fn new(seed: i64) -> Self {
+        Self {
+            seed: (seed ^ Self::MULTIPLIER) & Self::MASK,
+        }
+    }
+
+    /// Reset the seed, just like constructing a new instance.
+    pub fn set_seed(&mut self, seed: i64) {
+        self.seed = (seed ^ Self::MULTIPLIER) & Self::MASK;
+    }
+
+    /// Generate the next `bits` random bits.
+    pub fn next(&mut self, bits: u32) -> i32 {
+        self.seed = self
+            .seed
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT)
+            & Self::MASK;
+        (self.seed >> (48 - bits)) as i32
+    }
+
+    pub fn next_i32(&mut self) -> i32 {
+        self.next(32)
+    }
+
+    /// Generate an integer in `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `bound` is not positive.
+    pub fn next_i32_bounded(&mut self, bound: i32) -> i32 {
+        assert!(bound > 0, "Bound must be positive");
+
+        if bound & -bound == bound {
+            return ((bound as i64 * self.next(31) as i64) >> 31) as i32;
+        }
+
+        loop {
+            let bits = self.next(31);
+            let value = bits % bound;
+            if bits.wrapping_sub(value).wrapping_add(bound - 1) >= 0 {
+                return value;
+            }
+        }
+    }
+
+    pub fn next_i64(&mut self) -> i64 {
+        ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next(1) != 0
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        self.next(24) as f32 / (1 << 24) as f32
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        (((self.next(26) as i64) << 27) + self.next(27) as i64) as f64
+            * (1.0 / (1_i64 << 53) as f64)
+    }
+
+    /// Skip the given count of integers.
+    pub fn skip(&mut self, count: usize) {
+        for _ in 0..count {
+            self.next_i32();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_java_util_random() {
+        let mut random = JavaRandom::new(42);
+        assert_eq!(random.next_i32(), -1170105035);
+        assert_eq!(random.next_i32(), 234785527);
+        assert_eq!(random.next_i32_bounded(10), 8);
+        assert_eq!(random.next_i32_bounded(16), 0);
+        assert_eq!(random.next_i64(), 5694868678511409995);
+        assert!(!random.next_bool());
+        assert_eq!(random.next_f32(), 0.70771056);
+        assert_eq!(random.next_f64(), 0.6655489517945736);
+
+        random.set_seed(-1);
+        assert_eq!(random.next_i32(), 1155099827);
+        assert_eq!(random.next_i32_bounded(1000000007), 943952225);
+        assert_eq!(random.next_i64(), 226341162490527646);
+    }
+
+    #[test]
+    fn skip_consumes_integers() {
+        let mut a = JavaRandom::new(7);
+        let mut b = a.clone();
+        a.skip(3);
+        for _ in 0..3 {
+            b.next_i32();
+        }
+        assert_eq!(a.next_i64(), b.next_i64());
+    }
+}
@@ -0,0 +1,194 @@
+use std::ops::Deref;
+
+use crate::registry::{Registration, RegistryAccess, RegistryKey};
+
+/// Represents a biome.
+#[derive(Clone, Copy)]
+pub struct Biome {
+    id: usize,
+    pub temperature: f32,
+    pub downfall: f32,
+}
+
+impl Biome {
+    pub fn new(temperature: f32, downfall: f32) -> Self {
+        Self {
+            id: 0,
+            temperature,
+            downfall,
+        }
+    }
+}
+
+impl Registration for Biome {
+    fn accept(&mut self, id: usize) {
+        self.id = id
+    }
+
+    fn raw_id(&self) -> usize {
+        self.id
+    }
+}
+
+impl RegistryAccess for Biome {
+    fn registry() -> &'static crate::registry::Registry<Self> {
+        crate::registry::BIOME.deref()
+    }
+}
+
+impl Eq for Biome {}
+
+impl PartialEq for Biome {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl std::hash::Hash for Biome {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// A point in the multi-noise climate space.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct ClimateParameters {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub continentalness: f32,
+    pub erosion: f32,
+    pub depth: f32,
+    pub weirdness: f32,
+}
+
+impl ClimateParameters {
+    /// Parameters with all values set to `1.0`,
+    /// used as the default weights.
+    pub const ONE: Self = Self {
+        temperature: 1.0,
+        humidity: 1.0,
+        continentalness: 1.0,
+        erosion: 1.0,
+        depth: 1.0,
+        weirdness: 1.0,
+    };
+
+    /// Squared distance between this point and the other point,
+    /// with each parameter multiplied by its weight.
+    pub fn weighted_distance_squared(&self, other: &Self, weights: &Self) -> f32 {
+        fn d(a: f32, b: f32, w: f32) -> f32 {
+            let v = (a - b) * w;
+            v * v
+        }
+
+        d(self.temperature, other.temperature, weights.temperature)
+            + d(self.humidity, other.humidity, weights.humidity)
+            + d(
+                self.continentalness,
+                other.continentalness,
+                weights.continentalness,
+            )
+            + d(self.erosion, other.erosion, weights.erosion)
+            + d(self.depth, other.depth, weights.depth)
+            + d(self.weirdness, other.weirdness, weights.weirdness)
+    }
+}
+
+/// A biome source picking the biome whose parameter point
+/// is the nearest to the sampled climate.
+pub struct MultiNoiseBiomeSource {
+    entries: Vec<(ClimateParameters, RegistryKey<Biome>)>,
+    /// Weights of each parameter when measuring distances.
+    pub weights: ClimateParameters,
+}
+
+impl MultiNoiseBiomeSource {
+    pub fn new(entries: Vec<(ClimateParameters, RegistryKey<Biome>)>) -> Self {
+        Self {
+            entries,
+            weights: ClimateParameters::ONE,
+        }
+    }
+
+    /// Get the biome nearest to the given climate point,
+    /// or `None` if this source is empty.
+    pub fn get_biome(&self, point: &ClimateParameters) -> Option<&RegistryKey<Biome>> {
+        self.entries
+            .iter()
+            .map(|e| (e.0.weighted_distance_squared(point, &self.weights), &e.1))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|e| e.1)
+    }
+
+    pub fn entries(&self) -> &[(ClimateParameters, RegistryKey<Biome>)] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn key(path: &str) -> RegistryKey<Biome> {
+        RegistryKey::new(
+            &RegistryKey::of_reg(Identifier::parse("minecraft:worldgen/biome")),
+            Identifier::parse(path),
+        )
+    }
+
+    #[test]
+    fn nearest_biome() {
+        let cold = ClimateParameters {
+            temperature: -0.5,
+            ..Default::default()
+        };
+        let hot = ClimateParameters {
+            temperature: 0.8,
+            humidity: -0.2,
+            ..Default::default()
+        };
+        let mut source = MultiNoiseBiomeSource::new(vec![
+            (cold, key("minecraft:snowy_plains")),
+            (hot, key("minecraft:desert")),
+        ]);
+
+        let point = |temperature, humidity| ClimateParameters {
+            temperature,
+            humidity,
+            ..Default::default()
+        };
+        let get = |source: &MultiNoiseBiomeSource, point| {
+            source.get_biome(&point).unwrap().value().path().to_string()
+        };
+        assert_eq!(get(&source, point(-0.2, 0.0)), "snowy_plains");
+        assert_eq!(get(&source, point(0.5, 0.0)), "desert");
+
+        // humidity outweighs temperature with heavier weights
+        source.weights.humidity = 10.0;
+        assert_eq!(get(&source, point(0.5, 0.3)), "snowy_plains");
+
+        assert!(MultiNoiseBiomeSource::new(Vec::new())
+            .get_biome(&point(0.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn weighted_distance() {
+        let a = ClimateParameters::default();
+        let b = ClimateParameters {
+            erosion: 2.0,
+            weirdness: -1.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            a.weighted_distance_squared(&b, &ClimateParameters::ONE),
+            5.0
+        );
+        let weights = ClimateParameters {
+            erosion: 0.5,
+            ..ClimateParameters::ONE
+        };
+        assert_eq!(a.weighted_distance_squared(&b, &weights), 2.0);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::prelude::*;
+
+/// A square border of a world, limiting where blocks
+/// can be placed and entities can move.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WorldBorder {
+    /// Center of the border in (x, z).
+    pub center: glam::DVec2,
+    /// Side length of the border square.
+    pub diameter: f64,
+}
+
+impl WorldBorder {
+    /// The max diameter of a world border.
+    pub const MAX_DIAMETER: f64 = 5.9999968E7;
+
+    pub fn new(center: glam::DVec2, diameter: f64) -> Self {
+        Self { center, diameter }
+    }
+
+    pub fn min_x(&self) -> f64 {
+        self.center.x - self.diameter / 2.0
+    }
+
+    pub fn max_x(&self) -> f64 {
+        self.center.x + self.diameter / 2.0
+    }
+
+    pub fn min_z(&self) -> f64 {
+        self.center.y - self.diameter / 2.0
+    }
+
+    pub fn max_z(&self) -> f64 {
+        self.center.y + self.diameter / 2.0
+    }
+
+    /// Whether the given coords are inside this border.
+    pub fn contains(&self, x: f64, z: f64) -> bool {
+        x >= self.min_x() && x < self.max_x() && z >= self.min_z() && z < self.max_z()
+    }
+
+    /// Whether any part of the block at the given position is inside this border.
+    pub fn contains_block(&self, pos: BlockPos) -> bool {
+        let (x, z) = (pos.x as f64, pos.z as f64);
+        x + 1.0 > self.min_x() && x < self.max_x() && z + 1.0 > self.min_z() && z < self.max_z()
+    }
+
+    /// Distance from the given coords to the nearest edge of this border.
+    ///
+    /// The result is negative if the coords are outside this border.
+    pub fn distance_to(&self, x: f64, z: f64) -> f64 {
+        (x - self.min_x())
+            .min(self.max_x() - x)
+            .min(z - self.min_z())
+            .min(self.max_z() - z)
+    }
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center: glam::DVec2::ZERO,
+            diameter: Self::MAX_DIAMETER,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_is_half_open() {
+        let border = WorldBorder::new(glam::DVec2::ZERO, 10.0);
+        assert!(border.contains(-5.0, -5.0));
+        assert!(border.contains(4.999, 4.999));
+        assert!(!border.contains(5.0, 0.0));
+        assert!(!border.contains(0.0, -5.001));
+    }
+
+    #[test]
+    fn contains_block_accepts_partial_overlap() {
+        let border = WorldBorder::new(glam::DVec2::new(0.5, 0.5), 10.0);
+        assert!(border.contains_block(BlockPos::new(-5, 0, 0)));
+        assert!(border.contains_block(BlockPos::new(5, 0, 5)));
+        assert!(!border.contains_block(BlockPos::new(6, 0, 0)));
+        assert!(!border.contains_block(BlockPos::new(0, 0, -6)));
+    }
+
+    #[test]
+    fn distance_to_is_signed() {
+        let border = WorldBorder::new(glam::DVec2::ZERO, 10.0);
+        assert_eq!(border.distance_to(0.0, 0.0), 5.0);
+        assert_eq!(border.distance_to(4.0, -1.0), 1.0);
+        assert_eq!(border.distance_to(7.0, 0.0), -2.0);
+    }
+
+    #[test]
+    fn default_border_stops_short_of_horizontal_limit() {
+        let border = WorldBorder::default();
+        assert!(border.contains_block(BlockPos::new(29_999_983, 0, -29_999_984)));
+        assert!(!border.contains_block(BlockPos::new(29_999_984, 0, 0)));
+        assert!(!border.contains_block(BlockPos::new(0, 0, -30_000_000)));
+    }
+}
@@ -12,10 +12,786 @@ pub struct RawChunk<W: HeightLimitView> {
 
 impl<W: HeightLimitView> RawChunk<W> {}
 
+/// Data version of chunks saved by this crate.
+pub const DATA_VERSION: i32 = 3465;
+
+/// An upgrader of chunk nbt, receiving the data version of the nbt
+/// and returning the data version it upgraded the nbt to.
+pub type ChunkUpgrader =
+    std::sync::Arc<dyn Fn(i32, &mut crate::nbt::NbtCompound) -> anyhow::Result<i32> + Send + Sync>;
+
+/// Registered chunk upgraders and the data version they upgrade from.
+static UPGRADERS: parking_lot::RwLock<Vec<(i32, ChunkUpgrader)>> =
+    parking_lot::RwLock::new(Vec::new());
+
+/// Register an upgrader for chunk nbt saved in `from_version` or earlier,
+/// which returns the data version of the upgraded nbt.
+///
+/// Upgraders run in ascending order of their `from_version`
+/// when reading chunks through [`Chunk::read_nbt`].
+pub fn register_chunk_upgrader<F>(from_version: i32, f: F)
+where
+    F: Fn(i32, &mut crate::nbt::NbtCompound) -> anyhow::Result<i32> + Send + Sync + 'static,
+{
+    let mut upgraders = UPGRADERS.write();
+    let index = upgraders.partition_point(|e| e.0 <= from_version);
+    upgraders.insert(index, (from_version, std::sync::Arc::new(f)));
+}
+
+/// Run registered upgraders on the chunk nbt if its data version
+/// is below [`DATA_VERSION`], and update the version after that.
+///
+/// Each upgrader receives the version returned by the previous one,
+/// and upgraders for versions already passed are skipped.
+/// The upgraders are snapshotted first, so an upgrader may register
+/// other upgraders, which take effect from the next upgrade.
+pub fn upgrade_chunk_nbt(nbt: &mut crate::nbt::NbtCompound) -> anyhow::Result<()> {
+    let mut version = nbt.get_i32(Chunk::DATA_VERSION_KEY).unwrap_or_default();
+    if version >= DATA_VERSION {
+        return Ok(());
+    }
+
+    let upgraders = UPGRADERS
+        .read()
+        .iter()
+        .filter(|e| e.0 >= version && e.0 < DATA_VERSION)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for (from_version, upgrader) in upgraders {
+        if from_version >= version {
+            version = upgrader(version, nbt)?;
+        }
+    }
+
+    nbt.insert_i32(Chunk::DATA_VERSION_KEY, DATA_VERSION);
+    Ok(())
+}
+
+/// A chunk storing its sections from the bottom to the top.
+pub struct Chunk {
+    pub pos: ChunkPos,
+    bottom_section_coord: i32,
+    sections: Vec<ChunkSection>,
+    block_entities: hashbrown::HashMap<BlockPos, crate::nbt::NbtCompound>,
+    status: ChunkStatus,
+}
+
+impl Chunk {
+    const DATA_VERSION_KEY: &str = "DataVersion";
+    const X_POS_KEY: &str = "xPos";
+    const Y_POS_KEY: &str = "yPos";
+    const Z_POS_KEY: &str = "zPos";
+    const SECTIONS_KEY: &str = "sections";
+    const SECTION_Y_KEY: &str = "Y";
+    const BIOMES_KEY: &str = "biomes";
+    const BLOCK_ENTITIES_KEY: &str = "block_entities";
+    const STATUS_KEY: &str = "Status";
+    const X_NBT_KEY: &str = "x";
+    const Y_NBT_KEY: &str = "y";
+    const Z_NBT_KEY: &str = "z";
+
+    /// Creates an empty chunk with sections covering the given height limit,
+    /// in the [`ChunkStatus::Empty`] stage.
+    pub fn new(pos: ChunkPos, height_limit_view: &impl HeightLimitView) -> Self {
+        Self {
+            pos,
+            bottom_section_coord: height_limit_view.bottom_section_coord(),
+            sections: (0..height_limit_view.count_vertical_sections())
+                .map(|_| ChunkSection::new())
+                .collect(),
+            block_entities: hashbrown::HashMap::new(),
+            status: ChunkStatus::Empty,
+        }
+    }
+
+    /// The generation stage of this chunk.
+    pub fn status(&self) -> ChunkStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: ChunkStatus) {
+        self.status = status;
+    }
+
+    /// Write this chunk into nbt, which can be read back by [`Self::read_nbt`].
+    ///
+    /// Only the status, biomes of sections and block entities are written for now.
+    pub fn write_nbt(&self) -> crate::nbt::NbtCompound {
+        let mut nbt = crate::nbt::NbtCompound::new();
+        nbt.insert_i32(Self::DATA_VERSION_KEY, DATA_VERSION);
+        nbt.insert_i32(Self::X_POS_KEY, self.pos.x());
+        nbt.insert_i32(Self::Y_POS_KEY, self.bottom_section_coord);
+        nbt.insert_i32(Self::Z_POS_KEY, self.pos.z());
+        nbt.insert_str(Self::STATUS_KEY, &self.status.id().to_string());
+        self.write_biomes(&mut nbt);
+        self.write_block_entities(&mut nbt);
+        nbt
+    }
+
+    /// Read a chunk from its saved nbt, upgrading the nbt
+    /// first if it's saved in an older data version.
+    ///
+    /// Only the status, biomes of sections and block entities are read for now.
+    /// The status falls back to [`ChunkStatus::Empty`] if it's missing or invalid,
+    /// and a saved bottom section other than the one of `height_limit_view` fails.
+    pub fn read_nbt(
+        nbt: &mut crate::nbt::NbtCompound,
+        height_limit_view: &impl HeightLimitView,
+    ) -> anyhow::Result<Self> {
+        upgrade_chunk_nbt(nbt)?;
+
+        let (Some(x), Some(z)) = (nbt.get_i32(Self::X_POS_KEY), nbt.get_i32(Self::Z_POS_KEY))
+        else {
+            return Err(anyhow::anyhow!("Chunk position missing"));
+        };
+
+        let mut chunk = Self::new(ChunkPos::new(x, z), height_limit_view);
+        if let Some(y) = nbt.get_i32(Self::Y_POS_KEY) {
+            // sections are laid out for the height limit view
+            if y != chunk.bottom_section_coord {
+                return Err(anyhow::anyhow!(
+                    "Chunk bottom section {y} doesn't match the height limit bottom section {}",
+                    chunk.bottom_section_coord
+                ));
+            }
+        }
+        chunk.status = nbt
+            .get_str(Self::STATUS_KEY)
+            .and_then(|e| Identifier::try_parse(e).ok())
+            .and_then(|e| ChunkStatus::from_id(&e))
+            .unwrap_or_default();
+        chunk.read_biomes(nbt)?;
+        chunk.read_block_entities(nbt);
+        Ok(chunk)
+    }
+
+    /// Get nbt of the block entity at the target `pos`.
+    pub fn get_block_entity(&self, pos: BlockPos) -> Option<&crate::nbt::NbtCompound> {
+        self.block_entities.get(&pos)
+    }
+
+    /// Set nbt of the block entity at the target `pos`
+    /// and return the previous one.
+    pub fn set_block_entity(
+        &mut self,
+        pos: BlockPos,
+        nbt: crate::nbt::NbtCompound,
+    ) -> Option<crate::nbt::NbtCompound> {
+        self.block_entities.insert(pos, nbt)
+    }
+
+    /// Remove the block entity at the target `pos` and return it.
+    pub fn remove_block_entity(&mut self, pos: BlockPos) -> Option<crate::nbt::NbtCompound> {
+        self.block_entities.remove(&pos)
+    }
+
+    /// Write block entities of this chunk into the chunk nbt,
+    /// with each compound carrying its position.
+    pub fn write_block_entities(&self, nbt: &mut crate::nbt::NbtCompound) {
+        nbt.insert_list(
+            Self::BLOCK_ENTITIES_KEY,
+            self.block_entities
+                .iter()
+                .map(|(pos, be)| {
+                    let mut compound = be.clone();
+                    compound.insert_i32(Self::X_NBT_KEY, pos.x);
+                    compound.insert_i32(Self::Y_NBT_KEY, pos.y);
+                    compound.insert_i32(Self::Z_NBT_KEY, pos.z);
+                    crate::nbt::NbtElement::Compound(compound)
+                })
+                .collect(),
+        );
+    }
+
+    /// Read block entities from the chunk nbt into this chunk.
+    ///
+    /// Compounds without a valid position are skipped.
+    pub fn read_block_entities(&mut self, nbt: &crate::nbt::NbtCompound) {
+        for value in nbt.get_slice(Self::BLOCK_ENTITIES_KEY).unwrap_or_default() {
+            let crate::nbt::NbtElement::Compound(compound) = value else {
+                continue;
+            };
+
+            if let (Some(x), Some(y), Some(z)) = (
+                compound.get_i32(Self::X_NBT_KEY),
+                compound.get_i32(Self::Y_NBT_KEY),
+                compound.get_i32(Self::Z_NBT_KEY),
+            ) {
+                self.block_entities
+                    .insert(BlockPos::new(x, y, z), compound.clone());
+            }
+        }
+    }
+
+    /// Read biomes of sections from the chunk nbt into this chunk.
+    pub fn read_biomes(&mut self, nbt: &crate::nbt::NbtCompound) -> anyhow::Result<()> {
+        for section in nbt.get_slice(Self::SECTIONS_KEY).unwrap_or_default() {
+            let crate::nbt::NbtElement::Compound(section) = section else {
+                continue;
+            };
+
+            if let (Some(y), Some(biomes)) = (
+                section.get_i8(Self::SECTION_Y_KEY),
+                section.get_compound(Self::BIOMES_KEY),
+            ) {
+                let index = y as i32 - self.bottom_section_coord;
+                if index >= 0 && (index as usize) < self.sections.len() {
+                    self.sections[index as usize]
+                        .read_biomes(biomes, &crate::registry::BIOME.key)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write biomes of sections into the chunk nbt, merging
+    /// into existing section compounds with the same Y coord.
+    ///
+    /// Empty sections are skipped unless they have biomes set.
+    ///
+    /// Block states of sections aren't serialized yet,
+    /// so only their biomes are written here.
+    pub fn write_biomes(&self, nbt: &mut crate::nbt::NbtCompound) {
+        let mut sections = match nbt.remove(Self::SECTIONS_KEY) {
+            Some(crate::nbt::NbtElement::List(list)) => list,
+            _ => Vec::new(),
+        };
+
+        for (i, section) in self.sections.iter().enumerate() {
+            if section.is_empty() && section.biome_palette.is_empty() {
+                continue;
+            }
+
+            let y = (self.bottom_section_coord + i as i32) as i8;
+            let biomes = section.write_biomes();
+
+            let existing = sections.iter_mut().find_map(|e| match e {
+                crate::nbt::NbtElement::Compound(c) if c.get_i8(Self::SECTION_Y_KEY) == Some(y) => {
+                    Some(c)
+                }
+                _ => None,
+            });
+
+            if let Some(compound) = existing {
+                compound.insert_compound(Self::BIOMES_KEY, biomes);
+            } else {
+                let mut compound = crate::nbt::NbtCompound::new();
+                compound.insert_i8(Self::SECTION_Y_KEY, y);
+                compound.insert_compound(Self::BIOMES_KEY, biomes);
+                sections.push(crate::nbt::NbtElement::Compound(compound));
+            }
+        }
+
+        nbt.insert_list(Self::SECTIONS_KEY, sections);
+    }
+
+    /// Get biome at the quarter coords, which are block coords
+    /// divided by `4`, or `None` if it's not set or out of this chunk's height limit.
+    pub fn get_biome(&self, x: i32, y: i32, z: i32) -> Option<&BiomeKey> {
+        let index = self.section_index(y << 2)?;
+        self.sections[index].get_biome(x as u32 & 3, y as u32 & 3, z as u32 & 3)
+    }
+
+    /// Set biome at the quarter coords, which are block coords divided by `4`.
+    ///
+    /// Nothing happens if the coords are outside of this chunk's height limit.
+    pub fn set_biome(&mut self, x: i32, y: i32, z: i32, biome: BiomeKey) {
+        if let Some(index) = self.section_index(y << 2) {
+            self.sections[index].set_biome(x as u32 & 3, y as u32 & 3, z as u32 & 3, biome)
+        }
+    }
+
+    /// Index of the section containing the given y coord.
+    pub fn section_index(&self, y: i32) -> Option<usize> {
+        let index =
+            crate::util::math::ChunkSectionPos::section_coord(y) - self.bottom_section_coord;
+        if index >= 0 && (index as usize) < self.sections.len() {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Sections of this chunk, from the bottom to the top.
+    pub fn sections(&self) -> &[ChunkSection] {
+        &self.sections
+    }
+
+    /// Indices of sections with blocks changed since they were marked clean.
+    pub fn dirty_sections(&self) -> impl Iterator<Item = usize> + '_ {
+        self.sections
+            .iter()
+            .enumerate()
+            .filter(|e| e.1.is_dirty())
+            .map(|e| e.0)
+    }
+
+    /// Mutable sections of this chunk, from the bottom to the top.
+    pub fn sections_mut(&mut self) -> &mut [ChunkSection] {
+        &mut self.sections
+    }
+
+    /// Get block state at the target `pos`, or `None` if it's air
+    /// or outside of this chunk's height limit.
+    pub fn get_block_state(&self, pos: BlockPos) -> Option<crate::block::SharedBlockState> {
+        self.sections[self.section_index(pos.y)?].get_block_state(
+            pos.x as u32 & 15,
+            pos.y as u32 & 15,
+            pos.z as u32 & 15,
+        )
+    }
+
+    /// Whether blocks can be set in this chunk, which requires
+    /// the chunk to be at least in the [`ChunkStatus::Noise`] stage.
+    pub fn can_set_blocks(&self) -> bool {
+        self.status.is_at_least(ChunkStatus::Noise)
+    }
+
+    /// Set block state at the target `pos` and return the previous one,
+    /// where `None` stands for air.
+    ///
+    /// Nothing happens if the `pos` is outside of this chunk's height limit,
+    /// or blocks can't be set yet, see [`Self::can_set_blocks`].
+    pub fn set_block_state(
+        &mut self,
+        pos: BlockPos,
+        state: Option<crate::block::SharedBlockState>,
+    ) -> Option<crate::block::SharedBlockState> {
+        if !self.can_set_blocks() {
+            return None;
+        }
+
+        let index = self.section_index(pos.y)?;
+        self.sections[index].set_block_state(
+            pos.x as u32 & 15,
+            pos.y as u32 & 15,
+            pos.z as u32 & 15,
+            state,
+        )
+    }
+}
+
+impl HeightLimitView for Chunk {
+    fn bottom_y(&self) -> i32 {
+        self.bottom_section_coord << 4
+    }
+
+    fn top_y(&self) -> i32 {
+        (self.bottom_section_coord + self.sections.len() as i32) << 4
+    }
+}
+
+/// A standalone chunk being generated, decoupled from worlds.
+pub struct ProtoChunk {
+    chunk: Chunk,
+}
+
+impl ProtoChunk {
+    pub fn new(pos: ChunkPos, height_limit_view: &impl HeightLimitView) -> Self {
+        Self {
+            chunk: Chunk::new(pos, height_limit_view),
+        }
+    }
+
+    pub fn pos(&self) -> ChunkPos {
+        self.chunk.pos
+    }
+
+    /// The generation stage of this chunk.
+    pub fn status(&self) -> ChunkStatus {
+        self.chunk.status
+    }
+
+    pub fn set_status(&mut self, status: ChunkStatus) {
+        self.chunk.status = status;
+    }
+
+    /// Take the generated chunk out.
+    pub fn into_chunk(self) -> Chunk {
+        self.chunk
+    }
+}
+
+impl HeightLimitView for ProtoChunk {
+    fn bottom_y(&self) -> i32 {
+        self.chunk.bottom_y()
+    }
+
+    fn top_y(&self) -> i32 {
+        self.chunk.top_y()
+    }
+}
+
+impl super::BlockView for ProtoChunk {
+    fn block_state(&self, pos: BlockPos) -> Option<crate::block::SharedBlockState> {
+        if pos.chunk_pos() == self.chunk.pos {
+            self.chunk.get_block_state(pos)
+        } else {
+            None
+        }
+    }
+}
+
+impl super::MutableBlockView for ProtoChunk {
+    /// Set block state inside this chunk, ignoring the `flags`
+    /// since there are no neighbors or listeners.
+    fn set_block_state(
+        &mut self,
+        pos: BlockPos,
+        state: Option<crate::block::SharedBlockState>,
+        _flags: super::SetBlockFlags,
+    ) -> bool {
+        pos.chunk_pos() == self.chunk.pos
+            && self.chunk.can_set_blocks()
+            && self.chunk.section_index(pos.y).is_some()
+            && self.chunk.set_block_state(pos, state) != state
+    }
+}
+
+/// Stages of chunk generation, ordered from the first to the last.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum ChunkStatus {
+    #[default]
+    Empty,
+    StructureStarts,
+    Biomes,
+    Noise,
+    Surface,
+    Features,
+    Full,
+}
+
+impl ChunkStatus {
+    const VALUES: [Self; 7] = [
+        Self::Empty,
+        Self::StructureStarts,
+        Self::Biomes,
+        Self::Noise,
+        Self::Surface,
+        Self::Features,
+        Self::Full,
+    ];
+
+    /// The next stage of this status, or `None` if this is the last stage.
+    pub fn next(self) -> Option<Self> {
+        Self::VALUES.get(self as usize + 1).copied()
+    }
+
+    /// Whether this status is at the same or a later stage than the other status.
+    pub fn is_at_least(self, other: Self) -> bool {
+        self >= other
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChunkStatus::Empty => "empty",
+            ChunkStatus::StructureStarts => "structure_starts",
+            ChunkStatus::Biomes => "biomes",
+            ChunkStatus::Noise => "noise",
+            ChunkStatus::Surface => "surface",
+            ChunkStatus::Features => "features",
+            ChunkStatus::Full => "full",
+        }
+    }
+
+    /// The identifier of this status, as stored in the chunk nbt.
+    pub fn id(&self) -> Identifier {
+        Identifier::new(crate::util::VANILLA_NAMESPACE, self.as_str()).unwrap()
+    }
+
+    /// Get a status from its identifier.
+    pub fn from_id(id: &Identifier) -> Option<Self> {
+        Self::VALUES.into_iter().find(|e| e.as_str() == id.path())
+    }
+}
+
+impl EnumValues<7> for ChunkStatus {
+    fn values() -> [Self; 7] {
+        Self::VALUES
+    }
+}
+
+/// A 16*16*16 section of a chunk.
 pub struct ChunkSection {
     non_empty_block_count: AtomicU16,
     random_tickable_block_count: AtomicU16,
     non_empty_fluid_count: AtomicU16,
+    /// Block states in `y, z, x` order, where `None` stands for air.
+    /// The storage is not allocated if the section is empty.
+    block_states: Option<Vec<Option<crate::block::SharedBlockState>>>,
+    /// Whether blocks changed since the section was last meshed.
+    dirty: bool,
+    /// Biomes present in this section, indexed by [`Self::biomes`].
+    biome_palette: Vec<BiomeKey>,
+    /// Palette indices of biomes at quarter resolution in `y, z, x` order.
+    /// The storage is not allocated if the palette is empty.
+    biomes: Vec<u8>,
+}
+
+type BiomeKey = crate::registry::RegistryKey<super::biome::Biome>;
+
+impl ChunkSection {
+    /// Number of blocks in a section.
+    pub const VOLUME: usize = 16 * 16 * 16;
+    /// Number of biomes in a section, at quarter resolution.
+    pub const BIOME_VOLUME: usize = 4 * 4 * 4;
+
+    const PALETTE_KEY: &str = "palette";
+    const DATA_KEY: &str = "data";
+
+    /// Creates an empty section filled with air.
+    pub fn new() -> Self {
+        Self {
+            non_empty_block_count: AtomicU16::new(0),
+            random_tickable_block_count: AtomicU16::new(0),
+            non_empty_fluid_count: AtomicU16::new(0),
+            block_states: None,
+            dirty: false,
+            biome_palette: Vec::new(),
+            biomes: Vec::new(),
+        }
+    }
+
+    fn index(x: u32, y: u32, z: u32) -> usize {
+        ((y & 15) << 8 | (z & 15) << 4 | (x & 15)) as usize
+    }
+
+    /// Get block state at the local coords, or `None` if it's air.
+    pub fn get_block_state(
+        &self,
+        x: u32,
+        y: u32,
+        z: u32,
+    ) -> Option<crate::block::SharedBlockState> {
+        self.block_states
+            .as_ref()
+            .and_then(|e| e[Self::index(x, y, z)])
+    }
+
+    /// Set block state at the local coords and return the previous one,
+    /// where `None` stands for air.
+    pub fn set_block_state(
+        &mut self,
+        x: u32,
+        y: u32,
+        z: u32,
+        state: Option<crate::block::SharedBlockState>,
+    ) -> Option<crate::block::SharedBlockState> {
+        if self.block_states.is_none() && state.is_none() {
+            return None;
+        }
+
+        let states = self
+            .block_states
+            .get_or_insert_with(|| vec![None; Self::VOLUME]);
+        let previous = std::mem::replace(&mut states[Self::index(x, y, z)], state);
+        if previous != state {
+            self.dirty = true;
+        }
+
+        let count = self.non_empty_block_count.get_mut();
+        if previous.is_some() {
+            *count -= 1;
+        }
+        if state.is_some() {
+            *count += 1;
+        }
+
+        if *count == 0 {
+            self.block_states = None;
+        }
+
+        previous
+    }
+
+    /// Whether blocks changed since [`Self::mark_clean`] was called.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Mark this section as up to date, usually after meshing it.
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Number of non-air blocks in this section.
+    pub fn non_empty_block_count(&self) -> u16 {
+        self.non_empty_block_count
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Whether this section only contains air.
+    ///
+    /// Empty sections without biomes are skipped by [`Chunk::write_biomes`].
+    pub fn is_empty(&self) -> bool {
+        self.non_empty_block_count() == 0
+    }
+
+    /// Get biome at the local quarter coords in `0..4`,
+    /// or `None` if biomes are not set.
+    pub fn get_biome(&self, x: u32, y: u32, z: u32) -> Option<&BiomeKey> {
+        self.biomes
+            .get(Self::biome_index(x, y, z))
+            .map(|e| &self.biome_palette[*e as usize])
+    }
+
+    /// Set biome at the local quarter coords in `0..4`.
+    ///
+    /// The whole section is filled with the biome if no biomes are set yet.
+    pub fn set_biome(&mut self, x: u32, y: u32, z: u32, biome: BiomeKey) {
+        let index = match self.biome_palette.iter().position(|e| *e == biome) {
+            Some(index) => index,
+            None => {
+                if self.biome_palette.len() >= Self::BIOME_VOLUME {
+                    self.compact_biomes();
+                }
+                self.biome_palette.push(biome);
+                self.biome_palette.len() - 1
+            }
+        } as u8;
+
+        if self.biomes.is_empty() {
+            self.biomes = vec![index; Self::BIOME_VOLUME];
+        } else {
+            self.biomes[Self::biome_index(x, y, z)] = index;
+        }
+    }
+
+    /// Remove biomes no longer used by any cell from the palette.
+    ///
+    /// The palette holds at most [`Self::BIOME_VOLUME`] used biomes after
+    /// this, so indices always fit in a byte.
+    fn compact_biomes(&mut self) {
+        let mut used = vec![false; self.biome_palette.len()];
+        for e in self.biomes.iter() {
+            used[*e as usize] = true;
+        }
+
+        let mut remap = vec![0_u8; self.biome_palette.len()];
+        let mut palette = Vec::with_capacity(Self::BIOME_VOLUME);
+        for (i, biome) in std::mem::take(&mut self.biome_palette)
+            .into_iter()
+            .enumerate()
+        {
+            if used[i] {
+                remap[i] = palette.len() as u8;
+                palette.push(biome);
+            }
+        }
+
+        self.biome_palette = palette;
+        for e in self.biomes.iter_mut() {
+            *e = remap[*e as usize];
+        }
+    }
+
+    fn biome_index(x: u32, y: u32, z: u32) -> usize {
+        ((y & 3) << 4 | (z & 3) << 2 | (x & 3)) as usize
+    }
+
+    /// Write biomes of this section as a palette and packed palette indices.
+    ///
+    /// The packed data is omitted if there is only one biome in the palette.
+    pub fn write_biomes(&self) -> crate::nbt::NbtCompound {
+        let mut nbt = crate::nbt::NbtCompound::new();
+        nbt.insert_str_list(
+            Self::PALETTE_KEY,
+            &self
+                .biome_palette
+                .iter()
+                .map(|e| e.value().to_string())
+                .collect::<Vec<_>>()
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+        );
+
+        if self.biome_palette.len() > 1 {
+            let bits = Self::biome_bits(self.biome_palette.len());
+            let per_long = 64 / bits;
+            let mut data = vec![0_i64; Self::BIOME_VOLUME.div_ceil(per_long)];
+            for (i, e) in self.biomes.iter().enumerate() {
+                data[i / per_long] |= (*e as i64) << ((i % per_long) * bits);
+            }
+            nbt.insert_i64_slice(Self::DATA_KEY, &data);
+        }
+
+        nbt
+    }
+
+    /// Read biomes of this section written by [`Self::write_biomes`],
+    /// replacing existing biomes.
+    pub fn read_biomes(
+        &mut self,
+        nbt: &crate::nbt::NbtCompound,
+        registry: &crate::registry::RegistryKey<crate::registry::Registry<super::biome::Biome>>,
+    ) -> anyhow::Result<()> {
+        let palette = nbt
+            .get_slice(Self::PALETTE_KEY)
+            .unwrap_or_default()
+            .iter()
+            .map(|e| match e {
+                crate::nbt::NbtElement::String(id) => Ok(crate::registry::RegistryKey::new(
+                    registry,
+                    Identifier::try_parse(id)?,
+                )),
+                _ => Err(anyhow::anyhow!("Invalid biome in palette: {e:?}")),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if palette.len() > u8::MAX as usize + 1 {
+            return Err(anyhow::anyhow!("Too many biomes in palette"));
+        }
+
+        let biomes = match palette.len() {
+            0 => Vec::new(),
+            1 => vec![0; Self::BIOME_VOLUME],
+            len => {
+                let bits = Self::biome_bits(len);
+                let per_long = 64 / bits;
+                let data = nbt.get_i64_slice(Self::DATA_KEY).unwrap_or_default();
+                if data.len() < Self::BIOME_VOLUME.div_ceil(per_long) {
+                    return Err(anyhow::anyhow!("Biome data too short"));
+                }
+
+                let mut biomes = Vec::with_capacity(Self::BIOME_VOLUME);
+                for i in 0..Self::BIOME_VOLUME {
+                    let index = (data[i / per_long] >> ((i % per_long) * bits)) as usize
+                        & ((1 << bits) - 1);
+                    if index >= len {
+                        return Err(anyhow::anyhow!("Biome palette index {index} out of bounds"));
+                    }
+                    biomes.push(index as u8);
+                }
+                biomes
+            }
+        };
+
+        self.biome_palette = palette;
+        self.biomes = biomes;
+        Ok(())
+    }
+
+    /// Bits for each packed palette index.
+    fn biome_bits(palette_len: usize) -> usize {
+        (usize::BITS - (palette_len - 1).leading_zeros()) as usize
+    }
+
+    /// Whether all blocks in this section are the same.
+    pub fn is_uniform(&self) -> bool {
+        match &self.block_states {
+            Some(states) => states.iter().all(|e| *e == states[0]),
+            None => true,
+        }
+    }
+}
+
+impl Default for ChunkSection {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub struct UpgradeData {
@@ -141,3 +917,298 @@ pub mod palette {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A view with the overworld height limit.
+    struct View;
+
+    impl HeightLimitView for View {
+        fn bottom_y(&self) -> i32 {
+            -64
+        }
+
+        fn top_y(&self) -> i32 {
+            320
+        }
+    }
+
+    fn state() -> crate::block::SharedBlockState {
+        let block = crate::block::Block::new(Vec::new()).unwrap();
+        crate::state::States::get_shared(block.states, 0)
+    }
+
+    #[test]
+    fn section_empty_transitions() {
+        let state = state();
+        let mut section = ChunkSection::new();
+        assert!(section.is_empty());
+        assert!(section.is_uniform());
+
+        assert!(section.set_block_state(1, 2, 3, Some(state)).is_none());
+        assert!(!section.is_empty());
+        assert!(!section.is_uniform());
+        assert!(section.is_dirty());
+        assert_eq!(section.non_empty_block_count(), 1);
+
+        assert!(section.set_block_state(1, 2, 3, None) == Some(state));
+        assert!(section.is_empty());
+        assert!(section.is_uniform());
+        assert_eq!(section.non_empty_block_count(), 0);
+    }
+
+    fn biome(path: &str) -> BiomeKey {
+        crate::registry::RegistryKey::new(
+            &crate::registry::RegistryKey::of_reg(Identifier::parse("biome")),
+            Identifier::parse(path),
+        )
+    }
+
+    #[test]
+    fn biome_at_block_granularity() {
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0), &View);
+        assert!(chunk.get_biome(0, 0, 0).is_none());
+
+        // block (5, 9, 13) is in quarter (1, 2, 3)
+        chunk.set_biome(5 >> 2, 9 >> 2, 13 >> 2, biome("plains"));
+        chunk.set_biome(0, 2, 0, biome("desert"));
+
+        assert!(chunk.get_biome(5 >> 2, 9 >> 2, 13 >> 2) == Some(&biome("plains")));
+        assert!(chunk.get_biome(0, 2, 0) == Some(&biome("desert")));
+        // the rest of the section is filled with the first biome set
+        assert!(chunk.get_biome(3, 3, 3) == Some(&biome("plains")));
+        // outside of the height limit
+        assert!(chunk.get_biome(0, 320 >> 2, 0).is_none());
+    }
+
+    #[test]
+    fn biome_palette_compacts() {
+        let mut section = ChunkSection::new();
+        for i in 0..300 {
+            section.set_biome(0, 0, 0, biome(&format!("biome_{i}")));
+        }
+
+        assert!(section.biome_palette.len() <= ChunkSection::BIOME_VOLUME + 1);
+        assert!(section.get_biome(0, 0, 0) == Some(&biome("biome_299")));
+        assert!(section.get_biome(1, 0, 0) == Some(&biome("biome_0")));
+
+        let key = crate::registry::RegistryKey::of_reg(Identifier::parse("biome"));
+        let mut read = ChunkSection::new();
+        read.read_biomes(&section.write_biomes(), &key).unwrap();
+        assert!(read.get_biome(0, 0, 0) == Some(&biome("biome_299")));
+        assert!(read.get_biome(3, 3, 3) == Some(&biome("biome_0")));
+    }
+
+    #[test]
+    fn chained_upgrade() {
+        // upgraders stay registered for other tests, so they only
+        // touch keys of this test
+        register_chunk_upgrader(100, |version, nbt| {
+            if let Some(value) = nbt.remove("LegacyLevel") {
+                nbt.insert("MidLevel".to_string(), value);
+                nbt.insert_i32("FirstVersion", version);
+            }
+            // registering from inside an upgrader must not deadlock
+            register_chunk_upgrader(-1, |version, _| Ok(version));
+            Ok(200)
+        });
+        register_chunk_upgrader(200, |version, nbt| {
+            if let Some(value) = nbt.remove("MidLevel") {
+                nbt.insert("Level".to_string(), value);
+                nbt.insert_i32("SecondVersion", version);
+            }
+            Ok(300)
+        });
+
+        let mut nbt = crate::nbt::NbtCompound::new();
+        nbt.insert_i32(Chunk::DATA_VERSION_KEY, 50);
+        nbt.insert_i32("LegacyLevel", 7);
+        upgrade_chunk_nbt(&mut nbt).unwrap();
+
+        assert_eq!(nbt.get_i32("Level"), Some(7));
+        assert!(!nbt.contains_key("LegacyLevel"));
+        assert!(!nbt.contains_key("MidLevel"));
+        assert_eq!(nbt.get_i32("FirstVersion"), Some(50));
+        assert_eq!(nbt.get_i32("SecondVersion"), Some(200));
+        assert_eq!(nbt.get_i32(Chunk::DATA_VERSION_KEY), Some(DATA_VERSION));
+    }
+
+    #[test]
+    fn block_entity_round_trip() {
+        let pos = BlockPos::new(3, -20, 7);
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0), &View);
+        let mut be = crate::nbt::NbtCompound::new();
+        be.insert_str("id", "minecraft:chest");
+        assert!(chunk.set_block_entity(pos, be.clone()).is_none());
+        assert_eq!(chunk.get_block_entity(pos), Some(&be));
+
+        let mut nbt = chunk.write_nbt();
+        let read = Chunk::read_nbt(&mut nbt, &View).unwrap();
+        let read_be = read.get_block_entity(pos).unwrap();
+        assert_eq!(read_be.get_str("id"), Some("minecraft:chest"));
+        assert_eq!(read_be.get_i32("y"), Some(-20));
+
+        assert_eq!(chunk.remove_block_entity(pos), Some(be));
+        assert!(chunk.get_block_entity(pos).is_none());
+    }
+
+    #[test]
+    fn status_ordering() {
+        assert!(ChunkStatus::Empty < ChunkStatus::StructureStarts);
+        assert!(ChunkStatus::Noise < ChunkStatus::Full);
+        assert!(ChunkStatus::Full.is_at_least(ChunkStatus::Noise));
+        assert!(ChunkStatus::Noise.is_at_least(ChunkStatus::Noise));
+        assert!(!ChunkStatus::Biomes.is_at_least(ChunkStatus::Noise));
+        assert_eq!(ChunkStatus::Biomes.next(), Some(ChunkStatus::Noise));
+        assert_eq!(ChunkStatus::Full.next(), None);
+
+        let mut statuses = ChunkStatus::values();
+        statuses.reverse();
+        statuses.sort();
+        assert_eq!(statuses, ChunkStatus::values());
+    }
+
+    #[test]
+    fn set_block_requires_noise() {
+        use crate::world::{BlockView, MutableBlockView, SetBlockFlags};
+
+        let pos = BlockPos::new(0, 0, 0);
+        let flags = SetBlockFlags::NONE;
+        let mut chunk = ProtoChunk::new(ChunkPos::new(0, 0), &View);
+
+        chunk.set_status(ChunkStatus::Biomes);
+        assert!(!chunk.set_block_state(pos, Some(state()), flags));
+        assert!(chunk.block_state(pos).is_none());
+
+        chunk.set_status(ChunkStatus::Noise);
+        assert!(chunk.set_block_state(pos, Some(state()), flags));
+        assert!(chunk.block_state(pos).is_some());
+    }
+
+    #[test]
+    fn status_round_trip() {
+        let mut chunk = Chunk::new(ChunkPos::new(1, -2), &View);
+        chunk.set_status(ChunkStatus::Features);
+
+        let mut nbt = chunk.write_nbt();
+        assert_eq!(nbt.get_str(Chunk::STATUS_KEY), Some("minecraft:features"));
+        let read = Chunk::read_nbt(&mut nbt, &View).unwrap();
+        assert_eq!(read.status(), ChunkStatus::Features);
+        assert!(read.pos == ChunkPos::new(1, -2));
+
+        nbt.remove(Chunk::STATUS_KEY);
+        let read = Chunk::read_nbt(&mut nbt, &View).unwrap();
+        assert_eq!(read.status(), ChunkStatus::Empty);
+    }
+
+    #[test]
+    fn write_biomes_skips_empty_sections() {
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0), &View);
+        chunk.set_status(ChunkStatus::Full);
+        chunk.set_block_state(BlockPos::new(0, 16, 0), Some(state()));
+
+        let mut nbt = crate::nbt::NbtCompound::new();
+        chunk.write_biomes(&mut nbt);
+
+        let sections = nbt.get_slice(Chunk::SECTIONS_KEY).unwrap();
+        assert_eq!(sections.len(), 1);
+        let crate::nbt::NbtElement::Compound(section) = &sections[0] else {
+            panic!("section is not a compound");
+        };
+        assert_eq!(section.get_i8(Chunk::SECTION_Y_KEY), Some(1));
+    }
+
+    #[test]
+    fn setting_block_dirties_only_its_section() {
+        let state = state();
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0), &View);
+        chunk.set_status(ChunkStatus::Full);
+        assert_eq!(chunk.dirty_sections().count(), 0);
+
+        // y = 17 is in the section of index (16 - -64) / 16 = 5
+        chunk.set_block_state(BlockPos::new(3, 17, 4), Some(state));
+        assert_eq!(chunk.dirty_sections().collect::<Vec<_>>(), [5]);
+
+        chunk.sections[5].mark_clean();
+        assert_eq!(chunk.dirty_sections().count(), 0);
+
+        // setting the same state again doesn't dirty the section
+        chunk.set_block_state(BlockPos::new(3, 17, 4), Some(state));
+        assert_eq!(chunk.dirty_sections().count(), 0);
+
+        chunk.set_block_state(BlockPos::new(3, -64, 4), Some(state));
+        chunk.set_block_state(BlockPos::new(3, 17, 4), None);
+        assert_eq!(chunk.dirty_sections().collect::<Vec<_>>(), [0, 5]);
+    }
+
+    fn registered_state(path: &str) -> crate::block::SharedBlockState {
+        crate::registry::freeze_for_tests();
+        let block = *crate::registry::BLOCK
+            .get_from_id(&Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap())
+            .unwrap()
+            .1
+            .value();
+        crate::state::States::get_shared(block.states, 0)
+    }
+
+    /// Fill a stone floor at `floor_y` with a water layer above it.
+    fn generate_pool(view: &mut impl crate::world::MutableBlockView, floor_y: i32) {
+        let stone = registered_state("stone");
+        let water = registered_state("water");
+        for x in 0..16 {
+            for z in 0..16 {
+                view.set_block_state(
+                    BlockPos::new(x, floor_y, z),
+                    Some(stone),
+                    crate::world::SetBlockFlags::NONE,
+                );
+                view.set_block_state(
+                    BlockPos::new(x, floor_y + 1, z),
+                    Some(water),
+                    crate::world::SetBlockFlags::NONE,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generate_into_proto_chunk() {
+        use crate::world::BlockView;
+
+        let mut chunk = ProtoChunk::new(ChunkPos::new(0, 0), &View);
+        chunk.set_status(ChunkStatus::Noise);
+        generate_pool(&mut chunk, -64);
+
+        let floor = BlockPos::new(3, -64, 5);
+        let pool = BlockPos::new(3, -63, 5);
+        assert!(chunk.block_state(floor) == Some(registered_state("stone")));
+        assert!(chunk.block_state(pool) == Some(registered_state("water")));
+        assert!(chunk.block_state(BlockPos::new(3, -62, 5)).is_none());
+
+        assert!(chunk.fluid_state(floor).is_none());
+        let fluid = chunk.fluid_state(pool).unwrap();
+        assert_eq!(
+            crate::registry::FLUID
+                .get_from_raw(fluid.fluid().id())
+                .unwrap()
+                .key()
+                .value()
+                .path(),
+            "water"
+        );
+        assert!(chunk.fluid_state(BlockPos::new(3, -62, 5)).is_none());
+    }
+
+    #[test]
+    fn read_rejects_mismatched_bottom_section() {
+        let chunk = Chunk::new(ChunkPos::new(0, 0), &View);
+        let mut nbt = chunk.write_nbt();
+        assert_eq!(nbt.get_i32(Chunk::Y_POS_KEY), Some(-4));
+        assert!(Chunk::read_nbt(&mut nbt.clone(), &View).is_ok());
+
+        nbt.insert_i32(Chunk::Y_POS_KEY, 0);
+        assert!(Chunk::read_nbt(&mut nbt, &View).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use std::ops::Deref;
+
+use crate::registry::{Registration, RegistryAccess};
+
+use super::HeightLimitView;
+
+/// Describes properties of a dimension, like its vertical extent.
+#[derive(Clone, Copy, Debug)]
+pub struct DimensionType {
+    id: usize,
+    /// The bottom Y level, inclusive, of this dimension.
+    pub min_y: i32,
+    /// The vertical size, in blocks, of this dimension.
+    pub height: u32,
+    pub has_skylight: bool,
+    /// The max height that portals and chorus fruits can reach.
+    pub logical_height: u32,
+}
+
+impl DimensionType {
+    pub fn new(min_y: i32, height: u32, has_skylight: bool, logical_height: u32) -> Self {
+        Self {
+            id: 0,
+            min_y,
+            height,
+            has_skylight,
+            logical_height,
+        }
+    }
+
+    /// The vanilla overworld dimension type.
+    pub fn overworld() -> Self {
+        Self::new(-64, 384, true, 384)
+    }
+
+    /// The number of chunk sections, vertically, of this dimension.
+    pub fn section_count(&self) -> usize {
+        self.count_vertical_sections() as usize
+    }
+
+    /// The index of the chunk section containing the given Y level,
+    /// counted from the bottom section.
+    pub fn y_to_section_index(&self, y: i32) -> i32 {
+        crate::util::math::ChunkSectionPos::section_coord(y) - self.bottom_section_coord()
+    }
+}
+
+impl Default for DimensionType {
+    fn default() -> Self {
+        Self::overworld()
+    }
+}
+
+impl HeightLimitView for DimensionType {
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn bottom_y(&self) -> i32 {
+        self.min_y
+    }
+
+    fn top_y(&self) -> i32 {
+        self.min_y + self.height as i32
+    }
+}
+
+impl Registration for DimensionType {
+    fn accept(&mut self, id: usize) {
+        self.id = id
+    }
+
+    fn raw_id(&self) -> usize {
+        self.id
+    }
+}
+
+impl RegistryAccess for DimensionType {
+    fn registry() -> &'static crate::registry::Registry<Self> {
+        crate::registry::DIMENSION_TYPE.deref()
+    }
+}
+
+impl Eq for DimensionType {}
+
+impl PartialEq for DimensionType {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overworld_section_layout() {
+        let overworld = DimensionType::overworld();
+        assert_eq!(overworld.section_count(), 24);
+        assert_eq!(overworld.y_to_section_index(-64), 0);
+        assert_eq!(overworld.y_to_section_index(-49), 0);
+        assert_eq!(overworld.y_to_section_index(-48), 1);
+        assert_eq!(overworld.y_to_section_index(319), 23);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    prelude::*,
+    util::math::{BlockBox, ChunkPos},
+};
+
+/// A minimal entity with its position and data.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entity {
+    uuid: uuid::Uuid,
+    pub pos: glam::DVec3,
+    pub nbt: crate::nbt::NbtCompound,
+}
+
+impl Entity {
+    pub fn new(uuid: uuid::Uuid, pos: glam::DVec3, nbt: crate::nbt::NbtCompound) -> Self {
+        Self { uuid, pos, nbt }
+    }
+
+    pub fn uuid(&self) -> uuid::Uuid {
+        self.uuid
+    }
+
+    /// The position of the block this entity is in.
+    pub fn block_pos(&self) -> BlockPos {
+        BlockPos::from_vec3(self.pos)
+    }
+
+    /// The position of the chunk this entity is in.
+    pub fn chunk_pos(&self) -> ChunkPos {
+        self.block_pos().chunk_pos()
+    }
+}
+
+/// Storage of entities in a world, indexed by the chunks they are in.
+#[derive(Default)]
+pub struct EntityManager {
+    entities: hashbrown::HashMap<uuid::Uuid, Entity>,
+    chunk_index: hashbrown::HashMap<ChunkPos, Vec<uuid::Uuid>>,
+}
+
+impl EntityManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entity and return the previous entity with the same uuid.
+    pub fn add(&mut self, entity: Entity) -> Option<Entity> {
+        let previous = self.remove(entity.uuid);
+        self.chunk_index
+            .entry(entity.chunk_pos())
+            .or_default()
+            .push(entity.uuid);
+        self.entities.insert(entity.uuid, entity);
+        previous
+    }
+
+    /// Remove the entity with the given uuid and return it.
+    pub fn remove(&mut self, uuid: uuid::Uuid) -> Option<Entity> {
+        let entity = self.entities.remove(&uuid)?;
+        self.unindex(entity.chunk_pos(), uuid);
+        Some(entity)
+    }
+
+    pub fn get(&self, uuid: uuid::Uuid) -> Option<&Entity> {
+        self.entities.get(&uuid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.values()
+    }
+
+    /// Entities whose block positions are inside the given box.
+    pub fn entities_in_box(&self, bounds: BlockBox) -> Vec<&Entity> {
+        bounds
+            .chunk_positions()
+            .filter_map(|pos| self.chunk_index.get(&pos))
+            .flatten()
+            .filter_map(|uuid| self.entities.get(uuid))
+            .filter(|e| bounds.contains(e.block_pos()))
+            .collect()
+    }
+
+    /// Tick all entities with the given function,
+    /// moving entities between chunks in the index if needed.
+    pub fn tick_all<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Entity),
+    {
+        let mut moved = Vec::new();
+
+        for entity in self.entities.values_mut() {
+            let previous = entity.chunk_pos();
+            f(entity);
+            let current = entity.chunk_pos();
+            if previous != current {
+                moved.push((entity.uuid, previous, current));
+            }
+        }
+
+        for (uuid, previous, current) in moved {
+            self.unindex(previous, uuid);
+            self.chunk_index.entry(current).or_default().push(uuid);
+        }
+    }
+
+    fn unindex(&mut self, pos: ChunkPos, uuid: uuid::Uuid) {
+        if let Some(uuids) = self.chunk_index.get_mut(&pos) {
+            uuids.retain(|e| *e != uuid);
+            if uuids.is_empty() {
+                self.chunk_index.remove(&pos);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(id: u128, x: f64, z: f64) -> Entity {
+        Entity::new(
+            uuid::Uuid::from_u128(id),
+            glam::DVec3::new(x, 64.0, z),
+            Default::default(),
+        )
+    }
+
+    fn uuids(entities: Vec<&Entity>) -> Vec<uuid::Uuid> {
+        let mut uuids: Vec<_> = entities.into_iter().map(Entity::uuid).collect();
+        uuids.sort();
+        uuids
+    }
+
+    #[test]
+    fn entities_in_box_spans_chunks() {
+        let mut manager = EntityManager::new();
+        manager.add(entity(1, 1.5, 1.5));
+        manager.add(entity(2, 17.5, 1.5));
+        manager.add(entity(3, -20.5, 1.5));
+
+        let bounds = BlockBox::new(BlockPos::new(0, 0, 0), BlockPos::new(20, 100, 20));
+        assert_eq!(
+            uuids(manager.entities_in_box(bounds)),
+            [uuid::Uuid::from_u128(1), uuid::Uuid::from_u128(2)]
+        );
+    }
+
+    #[test]
+    fn tick_all_moves_entities_between_chunks() {
+        let mut manager = EntityManager::new();
+        manager.add(entity(1, 1.5, 1.5));
+
+        manager.tick_all(|e| e.pos.x += 32.0);
+
+        let old_chunk = BlockBox::new(BlockPos::new(0, 0, 0), BlockPos::new(15, 100, 15));
+        let new_chunk = BlockBox::new(BlockPos::new(32, 0, 0), BlockPos::new(47, 100, 15));
+        assert!(manager.entities_in_box(old_chunk).is_empty());
+        assert_eq!(manager.entities_in_box(new_chunk).len(), 1);
+        assert!(!manager.chunk_index.contains_key(&ChunkPos::new(0, 0)));
+    }
+
+    #[test]
+    fn re_adding_uuid_replaces_entity() {
+        let mut manager = EntityManager::new();
+        assert!(manager.add(entity(1, 1.5, 1.5)).is_none());
+
+        let previous = manager.add(entity(1, 40.5, 1.5)).unwrap();
+        assert_eq!(previous.pos.x, 1.5);
+        assert_eq!(manager.len(), 1);
+        assert_eq!(manager.chunk_index.len(), 1);
+        assert!(manager.chunk_index.contains_key(&ChunkPos::new(2, 0)));
+    }
+}
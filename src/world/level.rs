@@ -0,0 +1,166 @@
+use crate::{nbt::NbtCompound, prelude::*};
+
+/// Top-level properties of a world, stored in the `Data` compound of `level.dat`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LevelProperties {
+    pub spawn_pos: BlockPos,
+    /// Ticks passed in the world.
+    pub time: i64,
+    pub game_rules: hashbrown::HashMap<String, GameRuleValue>,
+    pub seed: i64,
+}
+
+impl LevelProperties {
+    const SPAWN_X_KEY: &str = "SpawnX";
+    const SPAWN_Y_KEY: &str = "SpawnY";
+    const SPAWN_Z_KEY: &str = "SpawnZ";
+    const TIME_KEY: &str = "Time";
+    const GAME_RULES_KEY: &str = "GameRules";
+    const WORLD_GEN_SETTINGS_KEY: &str = "WorldGenSettings";
+    const SEED_KEY: &str = "seed";
+
+    /// Read properties from the `Data` compound.
+    ///
+    /// Missing values fall back to their defaults, and game rules
+    /// which are neither booleans nor integers are skipped.
+    pub fn from_nbt(nbt: &NbtCompound) -> Self {
+        Self {
+            spawn_pos: BlockPos::new(
+                nbt.get_i32(Self::SPAWN_X_KEY).unwrap_or_default(),
+                nbt.get_i32(Self::SPAWN_Y_KEY).unwrap_or_default(),
+                nbt.get_i32(Self::SPAWN_Z_KEY).unwrap_or_default(),
+            ),
+            time: nbt.get_i64(Self::TIME_KEY).unwrap_or_default(),
+            game_rules: nbt
+                .get_compound(Self::GAME_RULES_KEY)
+                .map(|rules| {
+                    rules
+                        .iter()
+                        .filter_map(|(key, value)| match value {
+                            crate::nbt::NbtElement::String(value) => {
+                                GameRuleValue::parse(value).map(|e| (key.clone(), e))
+                            }
+                            _ => None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            seed: nbt
+                .get_compound(Self::WORLD_GEN_SETTINGS_KEY)
+                .and_then(|e| e.get_i64(Self::SEED_KEY))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Write properties into a `Data` compound.
+    pub fn to_nbt(&self) -> NbtCompound {
+        let mut nbt = NbtCompound::new();
+        nbt.insert_i32(Self::SPAWN_X_KEY, self.spawn_pos.x);
+        nbt.insert_i32(Self::SPAWN_Y_KEY, self.spawn_pos.y);
+        nbt.insert_i32(Self::SPAWN_Z_KEY, self.spawn_pos.z);
+        nbt.insert_i64(Self::TIME_KEY, self.time);
+
+        let mut rules = NbtCompound::new();
+        for (key, value) in self.game_rules.iter() {
+            rules.insert_str(key, &value.to_string());
+        }
+        nbt.insert(
+            Self::GAME_RULES_KEY.to_string(),
+            crate::nbt::NbtElement::Compound(rules),
+        );
+
+        let mut world_gen_settings = NbtCompound::new();
+        world_gen_settings.insert_i64(Self::SEED_KEY, self.seed);
+        nbt.insert(
+            Self::WORLD_GEN_SETTINGS_KEY.to_string(),
+            crate::nbt::NbtElement::Compound(world_gen_settings),
+        );
+
+        nbt
+    }
+}
+
+/// Value of a game rule, stored as a string in nbt.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameRuleValue {
+    Bool(bool),
+    Int(i32),
+}
+
+impl GameRuleValue {
+    /// Parse a game rule value from its string form.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "true" => Some(Self::Bool(true)),
+            "false" => Some(Self::Bool(false)),
+            _ => value.parse().ok().map(Self::Int),
+        }
+    }
+}
+
+impl std::fmt::Display for GameRuleValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameRuleValue::Bool(value) => value.fmt(f),
+            GameRuleValue::Int(value) => value.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_properties_round_trip() {
+        let properties = LevelProperties {
+            spawn_pos: BlockPos::new(-8, 64, 120),
+            time: 123456,
+            game_rules: [
+                ("doDaylightCycle".to_string(), GameRuleValue::Bool(false)),
+                ("randomTickSpeed".to_string(), GameRuleValue::Int(3)),
+            ]
+            .into_iter()
+            .collect(),
+            seed: -4172144997902289642,
+        };
+
+        let nbt = properties.to_nbt();
+        assert_eq!(nbt.get_i32("SpawnX"), Some(-8));
+        assert_eq!(
+            nbt.get_compound("GameRules")
+                .unwrap()
+                .get_str("doDaylightCycle"),
+            Some("false")
+        );
+        assert_eq!(
+            nbt.get_compound("WorldGenSettings")
+                .unwrap()
+                .get_i64("seed"),
+            Some(properties.seed)
+        );
+        assert_eq!(LevelProperties::from_nbt(&nbt), properties);
+    }
+
+    #[test]
+    fn level_properties_defaults() {
+        let mut rules = NbtCompound::new();
+        rules.insert_str("keepInventory", "true");
+        rules.insert_str("unknown", "maybe");
+        rules.insert_i32("notString", 1);
+        let mut nbt = NbtCompound::new();
+        nbt.insert(
+            "GameRules".to_string(),
+            crate::nbt::NbtElement::Compound(rules),
+        );
+
+        let properties = LevelProperties::from_nbt(&nbt);
+        assert_eq!(properties.spawn_pos, BlockPos::ORIGIN);
+        assert_eq!(properties.seed, 0);
+        assert_eq!(properties.game_rules.len(), 1);
+        assert_eq!(
+            properties.game_rules.get("keepInventory"),
+            Some(&GameRuleValue::Bool(true))
+        );
+    }
+}
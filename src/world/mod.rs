@@ -1,4 +1,10 @@
+pub mod biome;
+pub mod border;
 pub mod chunk;
+pub mod dimension;
+pub mod entity;
+pub mod level;
+pub mod noise;
 pub mod tick;
 
 use crate::prelude::*;
@@ -40,8 +46,21 @@ pub trait BlockView: HeightLimitView {
     /// Default max light level in Rimecraft.
     const DEF_MAX_LIGHT_LEVEL: u8 = 15;
 
-    /// The block state at the target `pos`.
-    fn block_state(&self, pos: BlockPos) -> &crate::block::BlockState;
+    /// The block state at the target `pos`, or `None` if it's air.
+    ///
+    /// This used to return `&BlockState`, which can't be borrowed from
+    /// storage shared behind locks. Implementors should return the
+    /// [`crate::block::SharedBlockState`] stored at `pos`, which is cheap
+    /// to copy, and callers should treat `None` as air.
+    fn block_state(&self, pos: BlockPos) -> Option<crate::block::SharedBlockState>;
+
+    /// The fluid state at the target `pos`, or `None` if it's empty.
+    ///
+    /// Fluid states are obtained from block states through [`crate::block::EVENTS`].
+    fn fluid_state(&self, pos: BlockPos) -> Option<crate::fluid::SharedFluidState> {
+        self.block_state(pos)
+            .and_then(|state| crate::block::EVENTS.read().fluid_state(&state))
+    }
 
     /// The max light level of this view.
     fn max_light_level() -> u8 {
@@ -49,8 +68,615 @@ pub trait BlockView: HeightLimitView {
     }
 }
 
+/// A [`BlockView`] which block states can be set into.
+pub trait MutableBlockView: BlockView {
+    /// Set block state at the target `pos`, where `None` stands for air,
+    /// and return whether the state changed.
+    fn set_block_state(
+        &mut self,
+        pos: BlockPos,
+        state: Option<crate::block::SharedBlockState>,
+        flags: SetBlockFlags,
+    ) -> bool;
+}
+
 /// Represents a view describing lights.
 pub trait LightSourceView: BlockView {
     /// Perform for each light sources in this view.
     fn for_each_sources<T: Fn(BlockPos, &crate::block::BlockState)>(&self, f: T);
 }
+
+/// A world containing chunks and entities.
+pub struct World {
+    /// The border of this world.
+    pub border: border::WorldBorder,
+    dimension: dimension::DimensionType,
+    chunks: hashbrown::HashMap<crate::util::math::ChunkPos, chunk::Chunk>,
+    /// Scheduled block ticks of this world.
+    pub block_ticks: Vec<tick::Tick<crate::block::Block>>,
+    updated_blocks: Vec<(BlockPos, SetBlockFlags)>,
+    /// Ticks passed in this world, driving the day-night cycle.
+    pub time: i64,
+    /// Entities in this world.
+    pub entities: entity::EntityManager,
+}
+
+impl World {
+    /// The max horizontal coord of blocks in a world.
+    pub const HORIZONTAL_LIMIT: i32 = 30000000;
+    /// Length of a day in ticks.
+    pub const TICKS_PER_DAY: i64 = 24000;
+    const NIGHT_START: i64 = 13000;
+
+    pub fn new(dimension: dimension::DimensionType) -> Self {
+        Self {
+            border: border::WorldBorder::default(),
+            dimension,
+            chunks: hashbrown::HashMap::new(),
+            block_ticks: Vec::new(),
+            updated_blocks: Vec::new(),
+            time: 0,
+            entities: entity::EntityManager::new(),
+        }
+    }
+
+    /// Advance the world clock by the given ticks.
+    pub fn advance_time(&mut self, ticks: i64) {
+        self.time += ticks;
+    }
+
+    /// Time of the current day, in `0..TICKS_PER_DAY`.
+    pub fn time_of_day(&self) -> i64 {
+        self.time.rem_euclid(Self::TICKS_PER_DAY)
+    }
+
+    /// Whether it's day time, before the night begins at tick `13000` of a day.
+    pub fn is_day(&self) -> bool {
+        self.time_of_day() < Self::NIGHT_START
+    }
+
+    pub fn is_night(&self) -> bool {
+        !self.is_day()
+    }
+
+    /// The moon phase, cycling from `0` (full moon) to `7` every 8 days.
+    pub fn moon_phase(&self) -> u8 {
+        (self.time.div_euclid(Self::TICKS_PER_DAY)).rem_euclid(8) as u8
+    }
+
+    /// The angle of the sky in `0.0..1.0` turns, where `0.0` is noon,
+    /// interpolated with the partial tick for rendering.
+    pub fn sky_angle(&self, partial_tick: f32) -> f32 {
+        let d = ((self.time_of_day() as f64 + partial_tick as f64) / Self::TICKS_PER_DAY as f64
+            - 0.25)
+            .rem_euclid(1.0);
+        let e = 0.5 - (d * std::f64::consts::PI).cos() / 2.0;
+        ((d * 2.0 + e) / 3.0) as f32
+    }
+
+    /// The dimension type of this world.
+    pub fn dimension(&self) -> &dimension::DimensionType {
+        &self.dimension
+    }
+
+    /// Whether the given position is inside the horizontal limit
+    /// and the world border, and inside the height limit of this world.
+    pub fn is_in_build_limit(&self, pos: BlockPos) -> bool {
+        pos.y >= self.bottom_y()
+            && pos.y < self.top_y()
+            && pos.x >= -Self::HORIZONTAL_LIMIT
+            && pos.x < Self::HORIZONTAL_LIMIT
+            && pos.z >= -Self::HORIZONTAL_LIMIT
+            && pos.z < Self::HORIZONTAL_LIMIT
+            && self.border.contains_block(pos)
+    }
+
+    /// Get the loaded chunk at the target `pos`.
+    pub fn chunk(&self, pos: crate::util::math::ChunkPos) -> Option<&chunk::Chunk> {
+        self.chunks.get(&pos)
+    }
+
+    /// Get the loaded chunk at the target `pos` mutably.
+    pub fn chunk_mut(&mut self, pos: crate::util::math::ChunkPos) -> Option<&mut chunk::Chunk> {
+        self.chunks.get_mut(&pos)
+    }
+
+    /// Load a chunk into this world and return the previous one at its position.
+    pub fn insert_chunk(&mut self, chunk: chunk::Chunk) -> Option<chunk::Chunk> {
+        self.chunks.insert(chunk.pos, chunk)
+    }
+
+    /// Get biome key at the target `pos`, or `None` if the chunk
+    /// is not loaded or the biome is not set.
+    pub fn get_biome(&self, pos: BlockPos) -> Option<&crate::registry::RegistryKey<biome::Biome>> {
+        self.chunk(pos.chunk_pos())?
+            .get_biome(pos.x >> 2, pos.y >> 2, pos.z >> 2)
+    }
+
+    /// Get block state at the target `pos`, or `None` if
+    /// it's air or the chunk is not loaded.
+    pub fn get_block_state(&self, pos: BlockPos) -> Option<crate::block::SharedBlockState> {
+        self.chunks.get(&pos.chunk_pos())?.get_block_state(pos)
+    }
+
+    /// Set block state at the target `pos`, where `None` stands for air,
+    /// and return whether the state changed.
+    ///
+    /// With [`SetBlockFlags::NOTIFY_NEIGHBORS`], ticks of the adjacent
+    /// non-air blocks are scheduled into [`Self::block_ticks`].
+    /// With [`SetBlockFlags::NOTIFY_LISTENERS`], the position is recorded
+    /// for listeners, see [`Self::drain_updated_blocks`].
+    pub fn set_block_state(
+        &mut self,
+        pos: BlockPos,
+        state: Option<crate::block::SharedBlockState>,
+        flags: SetBlockFlags,
+    ) -> bool {
+        use crate::util::math::Direction;
+
+        if !self.is_in_build_limit(pos) {
+            return false;
+        }
+
+        let Some(chunk) = self.chunks.get_mut(&pos.chunk_pos()) else {
+            return false;
+        };
+        if !chunk.can_set_blocks() || chunk.set_block_state(pos, state) == state {
+            return false;
+        }
+
+        if flags.contains(SetBlockFlags::NOTIFY_NEIGHBORS) {
+            for direction in Direction::values() {
+                let neighbor = pos.offset(direction);
+                if let Some(neighbor_state) = self.get_block_state(neighbor) {
+                    self.block_ticks
+                        .push(tick::Tick::new(neighbor_state.block(), neighbor));
+                }
+            }
+        }
+
+        if flags.contains(SetBlockFlags::NOTIFY_LISTENERS) {
+            self.updated_blocks.push((pos, flags));
+        }
+
+        true
+    }
+
+    /// Take all block updates recorded for listeners, such as
+    /// networking and rendering, along with their flags.
+    pub fn drain_updated_blocks(&mut self) -> std::vec::Drain<'_, (BlockPos, SetBlockFlags)> {
+        self.updated_blocks.drain(..)
+    }
+
+    /// Cast a ray from `start` to `end` through blocks, returning the first
+    /// block inside the build limit that matches the `predicate`, which receives
+    /// the block position and the index of the step reaching it.
+    ///
+    /// The traversal uses the fast voxel traversal algorithm by
+    /// Amanatides and Woo, visiting every block the ray passes through.
+    /// If `start` equals `end`, only the block containing it is tested,
+    /// and the hit side is [`Direction::Up`](crate::util::math::Direction::Up).
+    ///
+    /// Returns `None` if any coordinate is not finite.
+    pub fn raycast<F>(&self, start: glam::DVec3, end: glam::DVec3, predicate: F) -> Option<BlockHit>
+    where
+        F: Fn(BlockPos, u32) -> bool,
+    {
+        use crate::util::math::Direction;
+
+        if !start.is_finite() || !end.is_finite() {
+            return None;
+        }
+
+        let delta = end - start;
+
+        let mut pos = start.floor().as_ivec3();
+        let end_pos = end.floor().as_ivec3();
+        let step = delta.signum().as_ivec3();
+
+        // the side of the start block facing the opposite of the major axis
+        let abs = delta.abs();
+        let mut side = if delta == glam::DVec3::ZERO {
+            Direction::Up
+        } else if abs.x >= abs.y && abs.x >= abs.z {
+            if delta.x > 0.0 {
+                Direction::West
+            } else {
+                Direction::East
+            }
+        } else if abs.y >= abs.z {
+            if delta.y > 0.0 {
+                Direction::Down
+            } else {
+                Direction::Up
+            }
+        } else if delta.z > 0.0 {
+            Direction::North
+        } else {
+            Direction::South
+        };
+
+        let t_delta = glam::DVec3::new(
+            if delta.x == 0.0 {
+                f64::INFINITY
+            } else {
+                1.0 / abs.x
+            },
+            if delta.y == 0.0 {
+                f64::INFINITY
+            } else {
+                1.0 / abs.y
+            },
+            if delta.z == 0.0 {
+                f64::INFINITY
+            } else {
+                1.0 / abs.z
+            },
+        );
+
+        let boundary = |p: i32, s: i32, o: f64, d: f64| -> f64 {
+            if d == 0.0 {
+                f64::INFINITY
+            } else {
+                let b = if s > 0 { p as f64 + 1.0 } else { p as f64 };
+                (b - o) / d
+            }
+        };
+        let mut t_max = glam::DVec3::new(
+            boundary(pos.x, step.x, start.x, delta.x),
+            boundary(pos.y, step.y, start.y, delta.y),
+            boundary(pos.z, step.z, start.z, delta.z),
+        );
+
+        let mut t = 0.0;
+        let mut steps = 0;
+        loop {
+            let block_pos = BlockPos::from(pos);
+            if self.is_in_build_limit(block_pos) && predicate(block_pos, steps) {
+                return Some(BlockHit {
+                    pos: block_pos,
+                    side,
+                    hit: start + delta * t,
+                });
+            }
+
+            if pos == end_pos {
+                return None;
+            }
+            steps += 1;
+
+            if t_max.x <= t_max.y && t_max.x <= t_max.z {
+                t = t_max.x;
+                pos.x += step.x;
+                t_max.x += t_delta.x;
+                side = if step.x > 0 {
+                    Direction::West
+                } else {
+                    Direction::East
+                };
+            } else if t_max.y <= t_max.z {
+                t = t_max.y;
+                pos.y += step.y;
+                t_max.y += t_delta.y;
+                side = if step.y > 0 {
+                    Direction::Down
+                } else {
+                    Direction::Up
+                };
+            } else {
+                t = t_max.z;
+                pos.z += step.z;
+                t_max.z += t_delta.z;
+                side = if step.z > 0 {
+                    Direction::North
+                } else {
+                    Direction::South
+                };
+            }
+
+            if t > 1.0 {
+                return None;
+            }
+        }
+    }
+}
+
+/// Flags controlling side effects of [`World::set_block_state`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SetBlockFlags(u8);
+
+impl SetBlockFlags {
+    /// No side effects.
+    pub const NONE: Self = Self(0);
+    /// Schedule updates for neighbor blocks.
+    pub const NOTIFY_NEIGHBORS: Self = Self(1);
+    /// Notify listeners such as clients.
+    pub const NOTIFY_LISTENERS: Self = Self(2);
+    /// Tell listeners not to redraw the block.
+    pub const NO_REDRAW: Self = Self(4);
+    /// Both [`Self::NOTIFY_NEIGHBORS`] and [`Self::NOTIFY_LISTENERS`].
+    pub const NOTIFY_ALL: Self = Self(1 | 2);
+
+    /// Whether all flags of `other` are set in this one.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for SetBlockFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Result of a raycast hitting a block.
+#[derive(Clone, Copy, PartialEq)]
+pub struct BlockHit {
+    /// Position of the block hit.
+    pub pos: BlockPos,
+    /// The face of the block hit.
+    pub side: crate::util::math::Direction,
+    /// The exact point where the ray hit the block.
+    pub hit: glam::DVec3,
+}
+
+impl HeightLimitView for World {
+    fn height(&self) -> u32 {
+        self.dimension.height()
+    }
+
+    fn bottom_y(&self) -> i32 {
+        self.dimension.bottom_y()
+    }
+
+    fn top_y(&self) -> i32 {
+        self.dimension.top_y()
+    }
+}
+
+impl BlockView for World {
+    fn block_state(&self, pos: BlockPos) -> Option<crate::block::SharedBlockState> {
+        self.get_block_state(pos)
+    }
+}
+
+impl MutableBlockView for World {
+    fn set_block_state(
+        &mut self,
+        pos: BlockPos,
+        state: Option<crate::block::SharedBlockState>,
+        flags: SetBlockFlags,
+    ) -> bool {
+        World::set_block_state(self, pos, state, flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::math::Direction;
+
+    fn world() -> World {
+        World::new(dimension::DimensionType::overworld())
+    }
+
+    #[test]
+    fn raycast_along_each_axis() {
+        let world = world();
+        let origin = glam::DVec3::new(0.5, 0.5, 0.5);
+        let cases = [
+            (glam::DVec3::X, Direction::West),
+            (glam::DVec3::NEG_X, Direction::East),
+            (glam::DVec3::Y, Direction::Down),
+            (glam::DVec3::NEG_Y, Direction::Up),
+            (glam::DVec3::Z, Direction::North),
+            (glam::DVec3::NEG_Z, Direction::South),
+        ];
+
+        for (dir, side) in cases {
+            let target = BlockPos::from(dir.as_ivec3() * 3);
+            let visited = std::cell::RefCell::new(Vec::new());
+            let hit = world
+                .raycast(origin, origin + dir * 5.0, |pos, step| {
+                    visited.borrow_mut().push((pos, step));
+                    pos == target
+                })
+                .unwrap();
+
+            assert_eq!(hit.pos, target);
+            assert!(hit.side == side);
+            // the ray enters the target at the face 2.5 blocks away
+            assert!((hit.hit - (origin + dir * 2.5)).length() < 1e-9);
+            assert_eq!(
+                *visited.borrow(),
+                (0..4)
+                    .map(|e| (BlockPos::from(dir.as_ivec3() * e), e as u32))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn raycast_miss_and_build_limit() {
+        let world = world();
+        let start = glam::DVec3::new(0.5, 318.5, 0.5);
+        assert!(world
+            .raycast(start, start + glam::DVec3::X * 4.0, |_, _| false)
+            .is_none());
+
+        let visited = std::cell::RefCell::new(Vec::new());
+        let hit = world.raycast(start, start + glam::DVec3::Y * 4.0, |pos, _| {
+            visited.borrow_mut().push(pos.y);
+            true
+        });
+        assert_eq!(hit.map(|e| e.pos.y), Some(318));
+
+        // blocks above the build limit are never tested
+        let above = glam::DVec3::new(0.5, 320.5, 0.5);
+        assert!(world
+            .raycast(above, above + glam::DVec3::X * 2.0, |_, _| true)
+            .is_none());
+        assert_eq!(*visited.borrow(), [318]);
+    }
+
+    #[test]
+    fn raycast_same_block() {
+        let world = world();
+        let start = glam::DVec3::new(-0.5, 2.25, 7.75);
+        let hit = world
+            .raycast(start, start, |pos, step| {
+                pos == BlockPos::new(-1, 2, 7) && step == 0
+            })
+            .unwrap();
+        assert_eq!(hit.pos, BlockPos::new(-1, 2, 7));
+        assert!(hit.side == Direction::Up);
+        assert_eq!(hit.hit, start);
+
+        let end = glam::DVec3::new(-0.25, 2.5, 7.5);
+        let count = std::cell::Cell::new(0);
+        assert!(world
+            .raycast(start, end, |_, _| {
+                count.set(count.get() + 1);
+                false
+            })
+            .is_none());
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn raycast_rejects_non_finite() {
+        let world = world();
+        let start = glam::DVec3::new(0.5, 0.5, 0.5);
+        for end in [
+            glam::DVec3::new(f64::NAN, 0.5, 0.5),
+            glam::DVec3::new(0.5, f64::INFINITY, 0.5),
+            glam::DVec3::new(0.5, 0.5, f64::NEG_INFINITY),
+        ] {
+            assert!(world.raycast(start, end, |_, _| true).is_none());
+            assert!(world.raycast(end, start, |_, _| true).is_none());
+        }
+    }
+
+    fn state(path: &str) -> crate::block::SharedBlockState {
+        crate::registry::freeze_for_tests();
+        let block = *crate::registry::BLOCK
+            .get_from_id(&Identifier::new(crate::util::VANILLA_NAMESPACE, path).unwrap())
+            .unwrap()
+            .1
+            .value();
+        crate::state::States::get_shared(block.states, 0)
+    }
+
+    fn world_with_chunk() -> World {
+        let mut world = world();
+        let mut chunk = chunk::Chunk::new(crate::util::math::ChunkPos::new(0, 0), &world);
+        chunk.set_status(chunk::ChunkStatus::Full);
+        world.insert_chunk(chunk);
+        world
+    }
+
+    #[test]
+    fn set_block_state_notifies_neighbors_only_with_flag() {
+        let stone = state("stone");
+        let glass = state("glass");
+        let mut world = world_with_chunk();
+        let pos = BlockPos::new(1, 1, 1);
+        let neighbor = pos.offset(Direction::Up);
+
+        assert!(world.set_block_state(neighbor, Some(stone), SetBlockFlags::NONE));
+        assert!(world.block_ticks.is_empty());
+
+        assert!(world.set_block_state(pos, Some(glass), SetBlockFlags::NOTIFY_LISTENERS));
+        assert!(world.block_ticks.is_empty());
+        assert_eq!(
+            world.drain_updated_blocks().collect::<Vec<_>>(),
+            [(pos, SetBlockFlags::NOTIFY_LISTENERS)]
+        );
+
+        assert!(world.set_block_state(pos, Some(stone), SetBlockFlags::NOTIFY_NEIGHBORS));
+        assert_eq!(world.block_ticks.len(), 1);
+        assert_eq!(world.block_ticks[0].pos, neighbor);
+        assert!(world.block_ticks[0].value == stone.block());
+        assert_eq!(world.drain_updated_blocks().count(), 0);
+
+        // unchanged states have no side effects
+        assert!(!world.set_block_state(pos, Some(stone), SetBlockFlags::NOTIFY_ALL));
+        assert_eq!(world.block_ticks.len(), 1);
+        assert!(world.get_block_state(pos) == Some(stone));
+    }
+
+    #[test]
+    fn set_block_state_requires_loaded_chunk() {
+        let stone = state("stone");
+        let mut world = world_with_chunk();
+
+        assert!(!world.set_block_state(BlockPos::new(16, 0, 0), Some(stone), SetBlockFlags::NONE));
+        assert!(!world.set_block_state(BlockPos::new(0, 400, 0), Some(stone), SetBlockFlags::NONE));
+        assert!(world.get_block_state(BlockPos::new(0, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn day_night_thresholds() {
+        let mut world = world();
+        assert!(world.is_day());
+
+        world.advance_time(12999);
+        assert!(world.is_day());
+        world.advance_time(1);
+        assert!(world.is_night());
+        assert_eq!(world.time_of_day(), 13000);
+
+        world.advance_time(10999);
+        assert!(world.is_night());
+        world.advance_time(1);
+        assert!(world.is_day());
+        assert_eq!(world.time_of_day(), 0);
+        assert_eq!(world.time, World::TICKS_PER_DAY);
+
+        world.time = -1;
+        assert_eq!(world.time_of_day(), World::TICKS_PER_DAY - 1);
+        assert!(world.is_night());
+    }
+
+    #[test]
+    fn moon_phase_cycles_over_8_days() {
+        let mut world = world();
+        let phases = (0..9)
+            .map(|_| {
+                let phase = world.moon_phase();
+                world.advance_time(World::TICKS_PER_DAY);
+                phase
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(phases, [0, 1, 2, 3, 4, 5, 6, 7, 0]);
+
+        world.time = 3 * World::TICKS_PER_DAY + 23999;
+        assert_eq!(world.moon_phase(), 3);
+    }
+
+    #[test]
+    fn sky_angle_at_noon_and_midnight() {
+        let mut world = world();
+        world.time = 6000;
+        assert!(world.sky_angle(0.0).abs() < 1e-6);
+        world.time = 18000;
+        assert!((world.sky_angle(0.0) - 0.5).abs() < 1e-6);
+        assert!(world.sky_angle(0.5) > world.sky_angle(0.0));
+    }
+
+    #[test]
+    fn build_limit_covers_height_and_horizontal_bounds() {
+        let mut world = world();
+        assert!(world.is_in_build_limit(BlockPos::new(0, -64, 0)));
+        assert!(world.is_in_build_limit(BlockPos::new(0, 319, 0)));
+        assert!(!world.is_in_build_limit(BlockPos::new(0, -65, 0)));
+        assert!(!world.is_in_build_limit(BlockPos::new(0, 320, 0)));
+
+        world.border.diameter = 1.0E9;
+        assert!(world.is_in_build_limit(BlockPos::new(29_999_999, 0, -30_000_000)));
+        assert!(!world.is_in_build_limit(BlockPos::new(30_000_000, 0, 0)));
+        assert!(!world.is_in_build_limit(BlockPos::new(0, 0, -30_000_001)));
+    }
+}
@@ -0,0 +1,285 @@
+use crate::random::JavaRandom;
+
+const GRADIENTS: [[f64; 3]; 16] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+    [1.0, 1.0, 0.0],
+    [0.0, -1.0, 1.0],
+    [-1.0, 1.0, 0.0],
+    [0.0, -1.0, -1.0],
+];
+
+/// Improved perlin noise, producing the same values as vanilla.
+#[derive(Clone)]
+pub struct PerlinNoiseSampler {
+    permutation: [u8; 256],
+    pub origin_x: f64,
+    pub origin_y: f64,
+    pub origin_z: f64,
+}
+
+impl PerlinNoiseSampler {
+    /// Random integers consumed when creating a sampler.
+    const RANDOM_CALLS: usize = 262;
+
+    pub fn new(random: &mut JavaRandom) -> Self {
+        let origin_x = random.next_f64() * 256.0;
+        let origin_y = random.next_f64() * 256.0;
+        let origin_z = random.next_f64() * 256.0;
+
+        let mut permutation = [0_u8; 256];
+        for (i, e) in permutation.iter_mut().enumerate() {
+            *e = i as u8;
+        }
+        for i in 0..256 {
+            let j = random.next_i32_bounded(256 - i as i32) as usize;
+            permutation.swap(i, i + j);
+        }
+
+        Self {
+            permutation,
+            origin_x,
+            origin_y,
+            origin_z,
+        }
+    }
+
+    /// Sample the noise at the given coords.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let x = x + self.origin_x;
+        let y = y + self.origin_y;
+        let z = z + self.origin_z;
+        let (section_x, section_y, section_z) = (x.floor(), y.floor(), z.floor());
+        let (local_x, local_y, local_z) = (x - section_x, y - section_y, z - section_z);
+
+        self.sample_section(
+            section_x as i32,
+            section_y as i32,
+            section_z as i32,
+            local_x,
+            local_y,
+            local_z,
+        )
+    }
+
+    fn map(&self, input: i32) -> i32 {
+        self.permutation[(input & 0xff) as usize] as i32
+    }
+
+    fn sample_section(
+        &self,
+        section_x: i32,
+        section_y: i32,
+        section_z: i32,
+        local_x: f64,
+        local_y: f64,
+        local_z: f64,
+    ) -> f64 {
+        let i = self.map(section_x);
+        let j = self.map(section_x + 1);
+        let k = self.map(i + section_y);
+        let l = self.map(i + section_y + 1);
+        let m = self.map(j + section_y);
+        let n = self.map(j + section_y + 1);
+
+        let grad = |hash: i32, x: f64, y: f64, z: f64| {
+            let g = GRADIENTS[(hash & 15) as usize];
+            g[0] * x + g[1] * y + g[2] * z
+        };
+
+        let d = grad(self.map(k + section_z), local_x, local_y, local_z);
+        let e = grad(self.map(m + section_z), local_x - 1.0, local_y, local_z);
+        let f = grad(self.map(l + section_z), local_x, local_y - 1.0, local_z);
+        let g = grad(
+            self.map(n + section_z),
+            local_x - 1.0,
+            local_y - 1.0,
+            local_z,
+        );
+        let h = grad(self.map(k + section_z + 1), local_x, local_y, local_z - 1.0);
+        let o = grad(
+            self.map(m + section_z + 1),
+            local_x - 1.0,
+            local_y,
+            local_z - 1.0,
+        );
+        let p = grad(
+            self.map(l + section_z + 1),
+            local_x,
+            local_y - 1.0,
+            local_z - 1.0,
+        );
+        let q = grad(
+            self.map(n + section_z + 1),
+            local_x - 1.0,
+            local_y - 1.0,
+            local_z - 1.0,
+        );
+
+        let lerp = |delta: f64, start: f64, end: f64| start + delta * (end - start);
+        let (fx, fy, fz) = (fade(local_x), fade(local_y), fade(local_z));
+
+        lerp(
+            fz,
+            lerp(fy, lerp(fx, d, e), lerp(fx, f, g)),
+            lerp(fy, lerp(fx, h, o), lerp(fx, p, q)),
+        )
+    }
+}
+
+fn fade(value: f64) -> f64 {
+    value * value * value * (value * (value * 6.0 - 15.0) + 10.0)
+}
+
+/// Octaves of [`PerlinNoiseSampler`] combined with amplitudes,
+/// created the same way as the vanilla legacy octave sampler.
+#[derive(Clone)]
+pub struct OctavePerlinNoiseSampler {
+    samplers: Vec<Option<PerlinNoiseSampler>>,
+    amplitudes: Vec<f64>,
+    lacunarity: f64,
+    persistence: f64,
+}
+
+impl OctavePerlinNoiseSampler {
+    /// Creates a sampler with octaves from `first_octave` (usually negative)
+    /// and an amplitude for each octave, where zero amplitudes skip the octave.
+    ///
+    /// Octaves above `0` are not supported.
+    pub fn new(
+        random: &mut JavaRandom,
+        first_octave: i32,
+        amplitudes: Vec<f64>,
+    ) -> anyhow::Result<Self> {
+        let len = amplitudes.len() as i32;
+        let lowest = -first_octave;
+
+        if amplitudes.is_empty() {
+            return Err(anyhow::anyhow!("Need some octaves!"));
+        }
+        if lowest < len - 1 {
+            return Err(anyhow::anyhow!("Positive octaves are not supported"));
+        }
+
+        let mut samplers = vec![None; amplitudes.len()];
+        let sampler = PerlinNoiseSampler::new(random);
+        if lowest < len && amplitudes[lowest as usize] != 0.0 {
+            samplers[lowest as usize] = Some(sampler);
+        }
+
+        for i in (0..lowest).rev() {
+            if i < len && amplitudes[i as usize] != 0.0 {
+                samplers[i as usize] = Some(PerlinNoiseSampler::new(random));
+            } else {
+                random.skip(PerlinNoiseSampler::RANDOM_CALLS);
+            }
+        }
+
+        Ok(Self {
+            samplers,
+            lacunarity: 2_f64.powi(first_octave),
+            persistence: 2_f64.powi(len - 1) / (2_f64.powi(len) - 1.0),
+            amplitudes,
+        })
+    }
+
+    /// Sample the combined noise at the given coords.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut value = 0.0;
+        let mut lacunarity = self.lacunarity;
+        let mut persistence = self.persistence;
+
+        for (sampler, amplitude) in self.samplers.iter().zip(self.amplitudes.iter()) {
+            if let Some(sampler) = sampler {
+                value += amplitude
+                    * sampler.sample(
+                        maintain_precision(x * lacunarity),
+                        maintain_precision(y * lacunarity),
+                        maintain_precision(z * lacunarity),
+                    )
+                    * persistence;
+            }
+
+            lacunarity *= 2.0;
+            persistence /= 2.0;
+        }
+
+        value
+    }
+}
+
+/// Wrap large coords to keep precision of samplers.
+fn maintain_precision(value: f64) -> f64 {
+    value - (value / 3.3554432E7 + 0.5).floor() * 3.3554432E7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: [[f64; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [0.5, 1.25, -3.75],
+        [100.1, -64.3, 12345.6],
+        [-17.5, 63.0, 2.25],
+    ];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-12, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn perlin_known_values() {
+        let sampler = PerlinNoiseSampler::new(&mut JavaRandom::new(0));
+        assert_close(sampler.origin_x, 187.1277535684242);
+        assert_close(sampler.origin_y, 61.57732241190038);
+        assert_close(sampler.origin_z, 163.1788608896277);
+
+        let expected = [
+            -0.09566354243549174,
+            0.10732976966769597,
+            0.006952969869368192,
+            0.5362486201345023,
+        ];
+        for ([x, y, z], expected) in POINTS.into_iter().zip(expected) {
+            assert_close(sampler.sample(x, y, z), expected);
+        }
+
+        // lattice points relative to the origin are zero
+        let value = sampler.sample(
+            3.0 - sampler.origin_x.fract(),
+            -sampler.origin_y.fract(),
+            -sampler.origin_z.fract(),
+        );
+        assert!(value.abs() < 1e-9);
+    }
+
+    #[test]
+    fn octave_perlin_known_values() {
+        let sampler =
+            OctavePerlinNoiseSampler::new(&mut JavaRandom::new(1234), -3, vec![1.0, 1.0, 0.0, 1.0])
+                .unwrap();
+        let expected = [
+            -0.04777319304685494,
+            0.09119329725424416,
+            0.0447781512669745,
+            0.33556649567107977,
+        ];
+        for ([x, y, z], expected) in POINTS.into_iter().zip(expected) {
+            assert_close(sampler.sample(x, y, z), expected);
+        }
+
+        assert!(OctavePerlinNoiseSampler::new(&mut JavaRandom::new(0), 0, Vec::new()).is_err());
+        assert!(OctavePerlinNoiseSampler::new(&mut JavaRandom::new(0), 0, vec![1.0, 1.0]).is_err());
+    }
+}